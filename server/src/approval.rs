@@ -1,7 +1,7 @@
 use spacetimedb::{ReducerContext, Table, Identity, Timestamp, Filter, client_visibility_filter};
 use std::collections::HashSet;
 
-use crate::vote::{find_vote_by_id, find_vote_option_by_id, set_vote_option_approvals_count, vote_option_vote_id, vote_option_approvals_count, VotingSystem};
+use crate::vote::{find_vote_by_id, find_vote_option_by_id, set_vote_option_approvals_count, vote_option_vote_id, vote_option_approvals_count, is_voter_authorized, is_vote_closed, VotingSystem};
 // Bring the `approval` table trait into scope for method resolution on `ctx.db.approval()`.
 use self::approval as approval_table;
 
@@ -17,10 +17,10 @@ use self::approval as approval_table;
     index(name = by_vote_voter_option, btree(columns = [vote_id, voter, option_id]))
 )]
 pub struct Approval {
-    vote_id: u32,
-    option_id: u32,
-    voter: Identity,
-    ts: Timestamp,
+    pub vote_id: u32,
+    pub option_id: u32,
+    pub voter: Identity,
+    pub ts: Timestamp,
 }
 
 // RLS: a client may only see their own approval ballot rows
@@ -32,6 +32,10 @@ const APPROVAL_RLS: Filter = Filter::Sql(
 // Reducer: approve a single option
 #[spacetimedb::reducer]
 pub fn approve(ctx: &ReducerContext, vote_id: u32, option_id: u32) -> Result<(), String> {
+    approve_as(ctx, vote_id, option_id, ctx.sender)
+}
+
+fn approve_as(ctx: &ReducerContext, vote_id: u32, option_id: u32, voter: Identity) -> Result<(), String> {
     let Some(opt) = find_vote_option_by_id(ctx, option_id) else {
         return Err("Option not found".into());
     };
@@ -46,13 +50,19 @@ pub fn approve(ctx: &ReducerContext, vote_id: u32, option_id: u32) -> Result<(),
     if vote.voting_system != VotingSystem::Approval {
         return Err("This vote does not use approval voting".into());
     }
+    if !is_voter_authorized(ctx, &vote, voter) {
+        return Err("Not authorized to vote in this vote".into());
+    }
+    if is_vote_closed(ctx, &vote) {
+        return Err("This vote is closed".into());
+    }
 
     // Check if already approved
     if ctx
         .db
         .approval()
         .by_vote_voter_option()
-        .filter((vote_id, ctx.sender, option_id))
+        .filter((vote_id, voter, option_id))
         .next()
         .is_some()
     {
@@ -63,19 +73,29 @@ pub fn approve(ctx: &ReducerContext, vote_id: u32, option_id: u32) -> Result<(),
     ctx.db.approval().insert(Approval {
         vote_id,
         option_id,
-        voter: ctx.sender,
+        voter,
         ts: ctx.timestamp,
     });
+    crate::vote::append_ballot_event(ctx, vote_id, voter, crate::vote::BallotEventKind::Cast, Some(option_id), String::new());
 
     // Increment count
     let new_count = vote_option_approvals_count(&opt).saturating_add(1);
     set_vote_option_approvals_count(ctx, opt, new_count);
+
+    // A direct ballot can override an outgoing delegation via
+    // self-representation, which `delegation_weight` must reflect
+    // immediately rather than only on the next delegation change.
+    crate::vote::recompute_delegation_weights(ctx, vote_id);
     Ok(())
 }
 
 // Reducer: remove approval for a single option
 #[spacetimedb::reducer]
 pub fn unapprove(ctx: &ReducerContext, vote_id: u32, option_id: u32) -> Result<(), String> {
+    unapprove_as(ctx, vote_id, option_id, ctx.sender)
+}
+
+fn unapprove_as(ctx: &ReducerContext, vote_id: u32, option_id: u32, voter: Identity) -> Result<(), String> {
     let Some(opt) = find_vote_option_by_id(ctx, option_id) else {
         return Err("Option not found".into());
     };
@@ -90,18 +110,28 @@ pub fn unapprove(ctx: &ReducerContext, vote_id: u32, option_id: u32) -> Result<(
     if vote.voting_system != VotingSystem::Approval {
         return Err("This vote does not use approval voting".into());
     }
+    if !is_voter_authorized(ctx, &vote, voter) {
+        return Err("Not authorized to vote in this vote".into());
+    }
+    if is_vote_closed(ctx, &vote) {
+        return Err("This vote is closed".into());
+    }
 
     // Find approval row to delete
     if let Some(a) = ctx
         .db
         .approval()
         .by_vote_voter_option()
-        .filter((vote_id, ctx.sender, option_id))
+        .filter((vote_id, voter, option_id))
         .next()
     {
         ctx.db.approval().delete(a);
+        crate::vote::append_ballot_event(ctx, vote_id, voter, crate::vote::BallotEventKind::Withdraw, Some(option_id), String::new());
         let new_count = vote_option_approvals_count(&opt).saturating_sub(1);
         set_vote_option_approvals_count(ctx, opt, new_count);
+        // Withdrawing the voter's last direct ballot can revert them to an
+        // outgoing delegation - keep `delegation_weight` in sync either way.
+        crate::vote::recompute_delegation_weights(ctx, vote_id);
     }
     Ok(())
 }
@@ -109,6 +139,10 @@ pub fn unapprove(ctx: &ReducerContext, vote_id: u32, option_id: u32) -> Result<(
 // Reducer: set the full approval set for the caller for a given vote
 #[spacetimedb::reducer]
 pub fn set_approvals(ctx: &ReducerContext, vote_id: u32, option_ids: Vec<u32>) -> Result<(), String> {
+    set_approvals_as(ctx, vote_id, ctx.sender, option_ids)
+}
+
+fn set_approvals_as(ctx: &ReducerContext, vote_id: u32, voter: Identity, option_ids: Vec<u32>) -> Result<(), String> {
     // Validate vote exists
     // Validate vote exists and is of the correct type
     let Some(vote) = find_vote_by_id(ctx, vote_id) else {
@@ -117,6 +151,12 @@ pub fn set_approvals(ctx: &ReducerContext, vote_id: u32, option_ids: Vec<u32>) -
     if vote.voting_system != VotingSystem::Approval {
         return Err("This vote does not use approval voting".into());
     }
+    if !is_voter_authorized(ctx, &vote, voter) {
+        return Err("Not authorized to vote in this vote".into());
+    }
+    if is_vote_closed(ctx, &vote) {
+        return Err("This vote is closed".into());
+    }
 
     // Normalize option set and ensure they all belong to the vote
     let mut desired: HashSet<u32> = HashSet::new();
@@ -134,9 +174,9 @@ pub fn set_approvals(ctx: &ReducerContext, vote_id: u32, option_ids: Vec<u32>) -
         return Err("Cannot approve more than 20 options in a single vote".into());
     }
 
-    // Current approvals for caller on this vote
+    // Current approvals for the voter on this vote
     let mut current: HashSet<u32> = HashSet::new();
-    for a in ctx.db.approval().by_vote_and_voter().filter((vote_id, ctx.sender)) {
+    for a in ctx.db.approval().by_vote_and_voter().filter((vote_id, voter)) {
         current.insert(a.option_id);
     }
 
@@ -150,10 +190,11 @@ pub fn set_approvals(ctx: &ReducerContext, vote_id: u32, option_ids: Vec<u32>) -
             .db
             .approval()
             .by_vote_voter_option()
-            .filter((vote_id, ctx.sender, oid))
+            .filter((vote_id, voter, oid))
             .next()
         {
             ctx.db.approval().delete(a);
+            crate::vote::append_ballot_event(ctx, vote_id, voter, crate::vote::BallotEventKind::Withdraw, Some(oid), String::new());
             if let Some(opt) = find_vote_option_by_id(ctx, oid) {
                 let new_count = vote_option_approvals_count(&opt).saturating_sub(1);
                 set_vote_option_approvals_count(ctx, opt, new_count);
@@ -166,17 +207,145 @@ pub fn set_approvals(ctx: &ReducerContext, vote_id: u32, option_ids: Vec<u32>) -
         ctx.db.approval().insert(Approval {
             vote_id,
             option_id: oid,
-            voter: ctx.sender,
+            voter,
             ts: ctx.timestamp,
         });
+        crate::vote::append_ballot_event(ctx, vote_id, voter, crate::vote::BallotEventKind::Cast, Some(oid), String::new());
         if let Some(opt) = find_vote_option_by_id(ctx, oid) {
             let new_count = vote_option_approvals_count(&opt).saturating_add(1);
             set_vote_option_approvals_count(ctx, opt, new_count);
         }
     }
+    // The voter's set of direct ballots may have changed (including reverting
+    // to zero, which restores an outgoing delegation) - keep
+    // `delegation_weight` in sync.
+    crate::vote::recompute_delegation_weights(ctx, vote_id);
     Ok(())
 }
 
+// ================================
+// Delegated (proxy) approval voting
+// ================================
+
+// Delegation table: a delegator hands their ballot in a specific vote to a
+// delegatee. `Approval.voter` always records the delegator (the principal),
+// so tallies and `compute_approval_diffs` keep working unchanged - only
+// *who* is allowed to write those rows changes.
+#[spacetimedb::table(
+    name = delegation,
+    public,
+    index(name = by_vote_and_delegator, btree(columns = [vote_id, delegator])),
+    index(name = by_vote_and_delegatee, btree(columns = [vote_id, delegatee]))
+)]
+pub struct Delegation {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub delegator: Identity,
+    pub delegatee: Identity,
+    pub ts: Timestamp,
+}
+
+// RLS: a client sees delegations where it is either party
+#[client_visibility_filter]
+const DELEGATION_RLS: Filter = Filter::Sql(
+    "SELECT delegation.* FROM delegation WHERE delegation.delegator = :sender OR delegation.delegatee = :sender"
+);
+
+fn find_delegation(ctx: &ReducerContext, vote_id: u32, delegator: Identity) -> Option<Delegation> {
+    ctx.db
+        .delegation()
+        .by_vote_and_delegator()
+        .filter((vote_id, delegator))
+        .next()
+}
+
+fn is_delegating(ctx: &ReducerContext, vote_id: u32, identity: Identity) -> bool {
+    find_delegation(ctx, vote_id, identity).is_some()
+}
+
+/// Whether `identity` is already somebody's delegatee in `vote_id`.
+fn has_incoming_delegation(ctx: &ReducerContext, vote_id: u32, identity: Identity) -> bool {
+    ctx.db
+        .delegation()
+        .by_vote_and_delegatee()
+        .filter((vote_id, identity))
+        .next()
+        .is_some()
+}
+
+/// Delegate the caller's ballot for `vote_id` to `delegatee`. Replaces any
+/// existing delegation the caller has for this vote.
+#[spacetimedb::reducer]
+pub fn delegate_approval(ctx: &ReducerContext, vote_id: u32, delegatee: Identity) -> Result<(), String> {
+    if find_vote_by_id(ctx, vote_id).is_none() {
+        return Err("Vote not found".into());
+    }
+    if delegatee == ctx.sender {
+        return Err("Cannot delegate a vote to yourself".into());
+    }
+    // Reject chained delegation: the delegatee must not have delegated elsewhere.
+    if is_delegating(ctx, vote_id, delegatee) {
+        return Err("Cannot delegate to someone who has themselves delegated their vote".into());
+    }
+    // Reject chained delegation the other way around: the caller must not
+    // already be someone else's delegatee (otherwise their incoming
+    // delegator would transitively end up following this new delegation).
+    if has_incoming_delegation(ctx, vote_id, ctx.sender) {
+        return Err("Cannot delegate your vote while someone has delegated their vote to you".into());
+    }
+
+    if let Some(existing) = find_delegation(ctx, vote_id, ctx.sender) {
+        ctx.db.delegation().id().update(Delegation {
+            delegatee,
+            ts: ctx.timestamp,
+            ..existing
+        });
+    } else {
+        ctx.db.delegation().insert(Delegation {
+            id: 0,
+            vote_id,
+            delegator: ctx.sender,
+            delegatee,
+            ts: ctx.timestamp,
+        });
+    }
+    Ok(())
+}
+
+/// Revoke the caller's delegation for `vote_id`, if any.
+#[spacetimedb::reducer]
+pub fn revoke_delegation(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    if let Some(existing) = find_delegation(ctx, vote_id, ctx.sender) {
+        ctx.db.delegation().delete(existing);
+    }
+    Ok(())
+}
+
+fn assert_active_delegation(ctx: &ReducerContext, vote_id: u32, delegator: Identity) -> Result<(), String> {
+    match find_delegation(ctx, vote_id, delegator) {
+        Some(d) if d.delegatee == ctx.sender => Ok(()),
+        _ => Err("No active delegation from this voter to the caller".into()),
+    }
+}
+
+/// Approve a single option on behalf of `delegator`, who must have an
+/// active delegation to the caller for this vote.
+#[spacetimedb::reducer]
+pub fn approve_on_behalf(ctx: &ReducerContext, vote_id: u32, option_id: u32, delegator: Identity) -> Result<(), String> {
+    assert_active_delegation(ctx, vote_id, delegator)?;
+    approve_as(ctx, vote_id, option_id, delegator)
+}
+
+/// Set the complete approval set on behalf of `delegator`, who must have an
+/// active delegation to the caller for this vote.
+#[spacetimedb::reducer]
+pub fn set_approvals_on_behalf(ctx: &ReducerContext, vote_id: u32, delegator: Identity, option_ids: Vec<u32>) -> Result<(), String> {
+    assert_active_delegation(ctx, vote_id, delegator)?;
+    set_approvals_as(ctx, vote_id, delegator, option_ids)
+}
+
 // Note: SpacetimeDB reducers cannot return data directly.
 // Private tables are not accessible via client subscriptions.
 // We need to use optimistic UI updates and server-side validation.
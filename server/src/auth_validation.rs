@@ -1,11 +1,12 @@
-/// Optional: Server-side JWT validation for production deployments
-/// 
+/// Server-side JWT validation for production deployments
+///
 /// This module provides JWT token validation to ensure that users
-/// are who they claim to be. In production, you should:
-/// 1. Verify JWT signatures using provider's public keys
-/// 2. Validate claims (iss, sub, exp, aud)
-/// 3. Derive Identity and verify it matches the sender
-/// 
+/// are who they claim to be. Reducers run inside a sandboxed WASM host
+/// with no outbound network access, so we cannot hit a provider's
+/// `jwks_uri` at reducer time the way a normal backend would. Instead we
+/// store the provider's public keys in the database ourselves (pushed by
+/// an operator ahead of time) and verify signatures against those.
+///
 /// To use this, add to Cargo.toml:
 /// ```toml
 /// [dependencies]
@@ -14,8 +15,9 @@
 /// blake3 = "1.5"
 /// ```
 
-use spacetimedb::{Identity, ReducerContext};
+use spacetimedb::{Identity, ReducerContext, SpacetimeType, Table};
 use serde::{Deserialize, Serialize};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
 
 /// JWT Claims we care about
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,85 +32,218 @@ pub struct JwtClaims {
     pub name: Option<String>,
     /// Expiration time (Unix timestamp)
     pub exp: i64,
+    /// Not-before time (Unix timestamp, optional)
+    pub nbf: Option<i64>,
     /// Audience (optional)
     pub aud: Option<String>,
 }
 
+/// Signing algorithms we accept for JWKS keys. Kept deliberately narrow:
+/// these are the two algorithms the common OIDC providers (Google,
+/// Auth0, etc.) actually sign with.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JwksAlgorithm {
+    Rs256,
+    Es256,
+}
+
+impl JwksAlgorithm {
+    fn as_jsonwebtoken_algorithm(self) -> Algorithm {
+        match self {
+            JwksAlgorithm::Rs256 => Algorithm::RS256,
+            JwksAlgorithm::Es256 => Algorithm::ES256,
+        }
+    }
+}
+
+/// A public key pushed out-of-band by an operator, mirroring one entry of
+/// a provider's JWKS document. Looked up by `(issuer, kid)` since that's
+/// exactly how a client-side JWKS fetch would resolve a key for a token.
+#[spacetimedb::table(
+    name = jwks_key,
+    index(name = by_issuer_and_kid, btree(columns = [issuer, kid]))
+)]
+pub struct JwksKey {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub issuer: String,
+    pub kid: String,
+    pub algorithm: JwksAlgorithm,
+    /// PEM-encoded public key material (RSA or EC, depending on `algorithm`).
+    pub public_key_pem: String,
+    /// The audience we require a token signed by this key to have been
+    /// issued for. `None` means this provider's tokens carry no audience we
+    /// check (validation then skips the `aud` check entirely, same as a
+    /// provider that never sets `aud`). Never validated against the token's
+    /// own unverified `aud` claim - that would be a no-op check.
+    pub expected_audience: Option<String>,
+}
+
+/// Operators allowed to push JWKS keys. The first admin bootstraps itself;
+/// afterwards only existing admins may add more.
+#[spacetimedb::table(name = jwks_admin)]
+pub struct JwksAdmin {
+    #[primary_key]
+    identity: Identity,
+}
+
+fn is_jwks_admin(ctx: &ReducerContext, identity: Identity) -> bool {
+    ctx.db.jwks_admin().identity().find(identity).is_some()
+}
+
+/// Register `identity` as a JWKS admin. While no admin exists yet
+/// (bootstrap), the caller may only register itself - otherwise any
+/// unauthenticated caller could race to name someone else admin.
+/// Afterwards, an existing admin may register any identity.
+#[spacetimedb::reducer]
+pub fn register_jwks_admin(ctx: &ReducerContext, identity: Identity) -> Result<(), String> {
+    let has_admins = ctx.db.jwks_admin().iter().next().is_some();
+    if has_admins {
+        if !is_jwks_admin(ctx, ctx.sender) {
+            return Err("Only an existing JWKS admin can add another admin".into());
+        }
+    } else if identity != ctx.sender {
+        return Err("Bootstrap can only register the caller as the first admin".into());
+    }
+    if ctx.db.jwks_admin().identity().find(identity).is_none() {
+        ctx.db.jwks_admin().insert(JwksAdmin { identity });
+    }
+    Ok(())
+}
+
+/// Push (insert or replace) the public key for `(issuer, kid)`. Admin-only:
+/// this is the out-of-band substitute for a JWKS discovery fetch.
+#[spacetimedb::reducer]
+pub fn upsert_jwks_key(
+    ctx: &ReducerContext,
+    issuer: String,
+    kid: String,
+    algorithm: JwksAlgorithm,
+    public_key_pem: String,
+    expected_audience: Option<String>,
+) -> Result<(), String> {
+    if !is_jwks_admin(ctx, ctx.sender) {
+        return Err("Only a JWKS admin can push keys".into());
+    }
+
+    if let Some(existing) = ctx
+        .db
+        .jwks_key()
+        .by_issuer_and_kid()
+        .filter((issuer.as_str(), kid.as_str()))
+        .next()
+    {
+        ctx.db.jwks_key().id().update(JwksKey {
+            algorithm,
+            public_key_pem,
+            expected_audience,
+            ..existing
+        });
+    } else {
+        ctx.db.jwks_key().insert(JwksKey {
+            id: 0,
+            issuer,
+            kid,
+            algorithm,
+            public_key_pem,
+            expected_audience,
+        });
+    }
+    Ok(())
+}
+
+/// Validate a JWT's signature against the keys we have on file and return
+/// its claims.
+///
+/// Unlike a normal backend we never fetch `jwks_uri` ourselves; the
+/// key for the token's `kid`/`iss` must already have been pushed via
+/// `upsert_jwks_key`. An unknown `kid` is reported with a distinct error
+/// so operators can tell "bad token" apart from "we need to re-push keys
+/// because the provider rotated".
+pub fn validate_jwt(ctx: &ReducerContext, token: &str) -> Result<JwtClaims, String> {
+    let header = decode_header(token).map_err(|e| format!("Invalid JWT header: {}", e))?;
+    let kid = header
+        .kid
+        .clone()
+        .ok_or_else(|| "JWT header is missing a key id (kid)".to_string())?;
+
+    // We need the issuer to look up the key, but we don't know it's
+    // genuine until the signature is verified - so peek at the unverified
+    // payload just to read `iss`, then verify properly below.
+    let unverified_claims = decode_unverified_claims(token)?;
+
+    let Some(key) = ctx
+        .db
+        .jwks_key()
+        .by_issuer_and_kid()
+        .filter((unverified_claims.iss.as_str(), kid.as_str()))
+        .next()
+    else {
+        return Err(format!(
+            "Key rotation needed: no JWKS key on file for issuer '{}' and kid '{}'",
+            unverified_claims.iss, kid
+        ));
+    };
+
+    if key.algorithm.as_jsonwebtoken_algorithm() != header.alg {
+        return Err("JWT algorithm does not match the algorithm on file for this key".into());
+    }
+
+    let decoding_key = match key.algorithm {
+        JwksAlgorithm::Rs256 => DecodingKey::from_rsa_pem(key.public_key_pem.as_bytes()),
+        JwksAlgorithm::Es256 => DecodingKey::from_ec_pem(key.public_key_pem.as_bytes()),
+    }
+    .map_err(|e| format!("Invalid key material on file: {}", e))?;
+
+    let mut validation = Validation::new(key.algorithm.as_jsonwebtoken_algorithm());
+    validation.set_issuer(&[key.issuer.as_str()]);
+    validation.validate_nbf = true;
+    // Validate against the audience configured for this key, never against
+    // the token's own (unverified) `aud` claim - that would always match.
+    if let Some(expected) = &key.expected_audience {
+        validation.set_audience(&[expected.as_str()]);
+    } else {
+        validation.validate_aud = false;
+    }
+
+    let token_data = decode::<JwtClaims>(token, &decoding_key, &validation)
+        .map_err(|e| format!("Invalid token: {}", e))?;
+
+    let _ = ctx; // reducer context is only needed for the DB lookup above
+    Ok(token_data.claims)
+}
+
+/// Decode the JWT payload without verifying the signature, solely to read
+/// `iss` so we know which key to verify against. Never trust any other
+/// field from this - exp/nbf/aud are only authoritative once `validate_jwt`
+/// has checked the signature.
+fn decode_unverified_claims(token: &str) -> Result<JwtClaims, String> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err("Invalid JWT format".into());
+    }
+    let decoded = base64_url_decode(parts[1])
+        .map_err(|e| format!("Failed to decode payload: {}", e))?;
+    serde_json::from_slice(&decoded).map_err(|e| format!("Failed to parse claims: {}", e))
+}
+
 /// Derive SpacetimeDB Identity from JWT claims
 /// This matches the algorithm used by SpacetimeDB and the client
 pub fn derive_identity_from_claims(issuer: &str, subject: &str) -> Identity {
     // Concatenate issuer and subject with null byte separator
     let combined = format!("{}\0{}", issuer, subject);
-    
+
     // Hash using BLAKE3
     let hash = blake3::hash(combined.as_bytes());
-    
+
     // Return first 32 bytes as Identity
     let hash_bytes = hash.as_bytes();
     Identity::from_byte_array(*hash_bytes)
 }
 
-/// Validate JWT token and return claims
-/// 
-/// NOTE: This is a simplified example. For production:
-/// - Fetch and cache provider public keys (JWKS)
-/// - Validate signature using proper algorithm (RS256, ES256, etc.)
-/// - Check audience (aud) claim matches your app
-/// - Handle key rotation
-/// 
-/// Example with jsonwebtoken crate:
-/// ```rust
-/// use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
-/// 
-/// pub fn validate_jwt(token: &str, provider: &str) -> Result<JwtClaims, String> {
-///     // Get provider's public key (you'd fetch this from JWKS endpoint)
-///     let public_key = get_provider_public_key(provider)?;
-///     
-///     let decoding_key = DecodingKey::from_rsa_pem(public_key.as_bytes())
-///         .map_err(|e| format!("Invalid key: {}", e))?;
-///     
-///     let mut validation = Validation::new(Algorithm::RS256);
-///     validation.set_audience(&["your-client-id"]);
-///     
-///     let token_data = decode::<JwtClaims>(token, &decoding_key, &validation)
-///         .map_err(|e| format!("Invalid token: {}", e))?;
-///     
-///     Ok(token_data.claims)
-/// }
-/// ```
-pub fn validate_jwt_placeholder(token: &str) -> Result<JwtClaims, String> {
-    // PLACEHOLDER: In production, implement proper JWT validation
-    // For now, just parse without verification (INSECURE!)
-    
-    let parts: Vec<&str> = token.split('.').collect();
-    if parts.len() != 3 {
-        return Err("Invalid JWT format".into());
-    }
-    
-    // Decode base64url payload
-    let payload = parts[1];
-    let decoded = base64_url_decode(payload)
-        .map_err(|e| format!("Failed to decode payload: {}", e))?;
-    
-    let claims: JwtClaims = serde_json::from_slice(&decoded)
-        .map_err(|e| format!("Failed to parse claims: {}", e))?;
-    
-    // Check expiration
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-    
-    if claims.exp < now {
-        return Err("Token expired".into());
-    }
-    
-    Ok(claims)
-}
-
-/// Example: Authenticated reducer
-/// 
-/// This shows how to validate a JWT and verify the caller's identity
+/// Authenticated reducer: validate a JWT against our on-file JWKS keys,
+/// verify the caller's identity matches it, then create the vote.
 #[spacetimedb::reducer]
 pub fn authenticated_create_vote(
     ctx: &ReducerContext,
@@ -116,26 +251,26 @@ pub fn authenticated_create_vote(
     title: String,
     options: Vec<String>,
 ) -> Result<(), String> {
-    // 1. Validate JWT token
-    let claims = validate_jwt_placeholder(&jwt_token)?;
-    
+    // 1. Validate JWT token (signature, expiry, audience, issuer)
+    let claims = validate_jwt(ctx, &jwt_token)?;
+
     // 2. Derive expected Identity from claims
     let expected_identity = derive_identity_from_claims(&claims.iss, &claims.sub);
-    
+
     // 3. Verify caller matches derived identity
     if ctx.sender != expected_identity {
         return Err("Identity mismatch: JWT claims don't match sender".into());
     }
-    
+
     // 4. User is authenticated - proceed with vote creation
     log::info!(
         "Authenticated vote creation by {} ({})",
         claims.email.as_deref().unwrap_or("unknown"),
         claims.sub
     );
-    
+
     // Call the regular create_vote reducer
-    crate::vote::create_vote(ctx, title, options, None, None)
+    crate::vote::create_vote(ctx, title, options, None, None, None, None, None, None, None, None, None, None, None, None, None)
 }
 
 /// Base64URL decode helper
@@ -154,13 +289,13 @@ mod tests {
     fn test_derive_identity() {
         let issuer = "https://accounts.google.com";
         let subject = "123456789";
-        
+
         let identity = derive_identity_from_claims(issuer, subject);
-        
+
         // Identity should be deterministic
         let identity2 = derive_identity_from_claims(issuer, subject);
         assert_eq!(identity, identity2);
-        
+
         // Different subject should produce different identity
         let identity3 = derive_identity_from_claims(issuer, "different-user");
         assert_ne!(identity, identity3);
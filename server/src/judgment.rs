@@ -0,0 +1,572 @@
+use spacetimedb::{ReducerContext, SpacetimeType, Table, Identity, Filter, client_visibility_filter};
+
+use crate::vote::{find_vote_by_id, find_vote_option_by_id, get_vote_options, is_voter_authorized, is_vote_closed, VotingSystem};
+
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mention {
+    // Ordered from worst to best (lowest to highest)
+    Bad,
+    Inadequate,
+    Passable,
+    Fair,
+    Good,
+    VeryGood,
+    Excellent,
+}
+
+/// Parse a `Mention` back from its `Debug` formatting, as stored in
+/// `BallotEvent::payload` by the ballot audit trail.
+pub(crate) fn mention_from_str(s: &str) -> Option<Mention> {
+    match s {
+        "Bad" => Some(Mention::Bad),
+        "Inadequate" => Some(Mention::Inadequate),
+        "Passable" => Some(Mention::Passable),
+        "Fair" => Some(Mention::Fair),
+        "Good" => Some(Mention::Good),
+        "VeryGood" => Some(Mention::VeryGood),
+        "Excellent" => Some(Mention::Excellent),
+        _ => None,
+    }
+}
+
+/// A single judgment entry for batch submission
+#[derive(SpacetimeType, Clone, Debug)]
+pub struct JudgmentEntry {
+    pub option_id: u32,
+    pub mention: Mention,
+}
+
+// Judgments table: represents user's judgment ballots for specific options
+// This stores individual ballot ratings, not aggregated results
+// Public with RLS so each client only sees their own ballot rows
+#[spacetimedb::table(
+    name = judgment,
+    public,
+    index(name = by_option, btree(columns = [option_id])),
+    index(name = by_option_and_user, btree(columns = [option_id, voter]))
+)]
+pub struct Judgment {
+    #[primary_key]
+    #[auto_inc]
+    id: u64,
+    pub option_id: u32,
+    pub voter: Identity,
+    pub mention: Mention,
+}
+
+// RLS: a client may only see their own judgment ballot rows
+#[client_visibility_filter]
+const JUDGMENT_RLS: Filter = Filter::Sql(
+    "SELECT judgment.* FROM judgment WHERE judgment.voter = :sender"
+);
+
+// Precomputed summary for Majority Judgment per option
+#[spacetimedb::table(
+    name = mj_summary,
+    public,
+    index(name = by_vote, btree(columns = [vote_id]))
+)]
+pub struct MjSummary {
+    // Use option_id as primary key for convenient upserts
+    #[primary_key]
+    pub option_id: u32,
+    pub vote_id: u32,
+    pub total: u32,
+    // Counts per mention (ordered lowest to highest)
+    pub bad: u32,
+    pub inadequate: u32,
+    pub passable: u32,
+    pub fair: u32,
+    pub good: u32,
+    pub very_good: u32,
+    pub excellent: u32,
+    // Server stores only raw counts - ranking is computed into `mj_ranking` below.
+}
+
+/// Server-computed Majority Judgment ranking per option, so every client
+/// agrees on the winner without reimplementing the tie-break itself.
+/// Recomputed from scratch alongside `mj_summary`.
+#[spacetimedb::table(
+    name = mj_ranking,
+    public,
+    index(name = by_vote, btree(columns = [vote_id]))
+)]
+pub struct MjRanking {
+    // Use option_id as primary key for convenient upserts
+    #[primary_key]
+    pub option_id: u32,
+    pub vote_id: u32,
+    /// 1-based dense rank - tied options share a rank, and the next
+    /// distinct rank has no gap. Options with zero ballots always rank
+    /// last, sharing a rank with each other.
+    pub rank: u32,
+    /// The option's median mention ("majority grade"). `None` for options
+    /// with zero ballots.
+    pub majority_grade: Option<Mention>,
+    /// Set when this option shared its rank with at least one other option
+    /// and `Vote::tie_strategy` had to pick an order between them. `None`
+    /// when this option's rank was not tied with anyone.
+    pub tie_broken_by: Option<crate::tie_break::TieStrategy>,
+}
+
+fn mention_from_index(idx: usize) -> Mention {
+    match idx {
+        0 => Mention::Bad,
+        1 => Mention::Inadequate,
+        2 => Mention::Passable,
+        3 => Mention::Fair,
+        4 => Mention::Good,
+        5 => Mention::VeryGood,
+        _ => Mention::Excellent,
+    }
+}
+
+fn mention_to_index(m: Mention) -> usize {
+    match m {
+        Mention::Bad => 0,
+        Mention::Inadequate => 1,
+        Mention::Passable => 2,
+        Mention::Fair => 3,
+        Mention::Good => 4,
+        Mention::VeryGood => 5,
+        Mention::Excellent => 6,
+    }
+}
+
+/// The "majority gauge" used for usual-judgment tie-breaking: the median
+/// grade (lower median: the grade at sorted position `ceil(total/2)`),
+/// the count of ballots strictly above it (`p`), and the count strictly
+/// below it (`q`). `None` for an option with zero ballots.
+fn majority_gauge(counts: &[u32; 7], total: u32) -> Option<(Mention, u32, u32)> {
+    if total == 0 {
+        return None;
+    }
+    let target = total.div_ceil(2);
+    let mut cumulative = 0u32;
+    let mut median_idx = 0usize;
+    for (idx, &c) in counts.iter().enumerate() {
+        cumulative += c;
+        if cumulative >= target {
+            median_idx = idx;
+            break;
+        }
+    }
+    let below: u32 = counts[..median_idx].iter().sum();
+    let above: u32 = counts[median_idx + 1..].iter().sum();
+    Some((mention_from_index(median_idx), above, below))
+}
+
+/// Order two majority gauges best-first: options with no ballots rank last;
+/// among options with ballots, higher median wins; among equal medians, a
+/// `p > q` gauge beats a `q >= p` one, then larger `p` (for `p > q`) or
+/// smaller `q` (for `q >= p`) wins. Equal gauges are a true tie.
+pub(crate) fn compare_gauge(a: &Option<(Mention, u32, u32)>, b: &Option<(Mention, u32, u32)>) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some((m1, p1, q1)), Some((m2, p2, q2))) => {
+            let by_median = m2.cmp(m1);
+            if by_median != Ordering::Equal {
+                return by_median;
+            }
+            let group = |p: u32, q: u32| if p > q { 0u8 } else { 1u8 };
+            let by_group = group(*p1, *q1).cmp(&group(*p2, *q2));
+            if by_group != Ordering::Equal {
+                return by_group;
+            }
+            if *p1 > *q1 {
+                p2.cmp(p1)
+            } else {
+                q1.cmp(q2)
+            }
+        }
+    }
+}
+
+/// Dense-rank `gauges` best-first (tied options share a rank), breaking ties
+/// per `strategy` by comparing each tied option's ballot count at the
+/// next-higher grade (the "earlier stage" for MJ's usual-judgment
+/// tie-break). Returns, per option: its rank, majority grade, and (when it
+/// shared its rank with another option) the tie-break strategy used.
+/// Shared by the live `recompute_mj_ranking` and by `vote::finalize_vote`'s
+/// delegation-aware winner determination, so both agree on what "winner"
+/// means.
+pub(crate) fn dense_rank_options(
+    vote_id: u32,
+    strategy: crate::tie_break::TieStrategy,
+    mut gauges: Vec<(u32, Option<(Mention, u32, u32)>, [u32; 7])>,
+) -> Vec<(u32, u32, Option<Mention>, Option<crate::tie_break::TieStrategy>)> {
+    gauges.sort_by(|a, b| compare_gauge(&a.1, &b.1));
+
+    let mut result = Vec::with_capacity(gauges.len());
+    let mut idx = 0;
+    let mut rank: u32 = 0;
+    while idx < gauges.len() {
+        let mut end = idx + 1;
+        while end < gauges.len() && compare_gauge(&gauges[idx].1, &gauges[end].1) == std::cmp::Ordering::Equal {
+            end += 1;
+        }
+        rank += 1;
+
+        let group = &gauges[idx..end];
+        let group_ids: Vec<u32> = group.iter().map(|(id, _, _)| *id).collect();
+        let tied = group_ids.len() > 1;
+        let order = if tied {
+            crate::tie_break::resolve_tie(vote_id, &group_ids, strategy, |id| {
+                let (_, gauge, counts) = group.iter().find(|(gid, _, _)| *gid == id).unwrap();
+                let next_idx = gauge.map_or(0, |(m, _, _)| mention_to_index(m) + 1);
+                counts.get(next_idx).copied().unwrap_or(0) as i64
+            })
+        } else {
+            group_ids
+        };
+
+        for option_id in order {
+            let (_, gauge, _) = group.iter().find(|(gid, _, _)| *gid == option_id).unwrap();
+            result.push((option_id, rank, gauge.map(|(m, _, _)| m), if tied { Some(strategy) } else { None }));
+        }
+
+        idx = end;
+    }
+    result
+}
+
+/// Delete-then-reinsert `mj_ranking` for `vote_id` from `dense_rank_options`'
+/// output.
+fn recompute_mj_ranking(ctx: &ReducerContext, vote_id: u32, gauges: Vec<(u32, Option<(Mention, u32, u32)>, [u32; 7])>) {
+    for row in ctx.db.mj_ranking().by_vote().filter(vote_id) {
+        ctx.db.mj_ranking().delete(row);
+    }
+
+    let strategy = find_vote_by_id(ctx, vote_id)
+        .and_then(|v| v.tie_strategy)
+        .unwrap_or(crate::tie_break::TieStrategy::Forwards);
+
+    for (option_id, rank, majority_grade, tie_broken_by) in dense_rank_options(vote_id, strategy, gauges) {
+        ctx.db.mj_ranking().insert(MjRanking {
+            option_id,
+            vote_id,
+            rank,
+            majority_grade,
+            tie_broken_by,
+        });
+    }
+}
+
+// `mj_summary`/`mj_ranking` intentionally count only direct judgment
+// ballots, never delegated ones. Delegation is resolved exactly once,
+// generically, at close/outcome time via
+// `vote::compute_effective_tally`/`judgment::effective_mj_gauges` (the
+// tally machinery is shared with Approval and STV). Folding delegation
+// weight in here too would double count every delegated ballot once
+// `effective_mj_gauges` adds it again on top of an already-inflated
+// `mj_summary`, and would also make
+// `audit_judgment`'s replay-from-`ballot_event` (which never sees
+// delegation-derived ballots) falsely flag tampering on any vote using
+// delegation. See `vote::recompute_delegation_weights`, which only updates
+// the `delegation_weight` display table and deliberately does not touch
+// `mj_summary`.
+pub(crate) fn recompute_mj_summary_for_vote(ctx: &ReducerContext, vote_id: u32) {
+    let mut gauges: Vec<(u32, Option<(Mention, u32, u32)>, [u32; 7])> = Vec::new();
+
+    for opt in get_vote_options(ctx, vote_id) {
+        let mut counts = [0u32; 7];
+        let mut total: u32 = 0;
+        for j in ctx.db.judgment().by_option().filter(opt.id) {
+            match j.mention {
+                Mention::Bad => counts[0] += 1,
+                Mention::Inadequate => counts[1] += 1,
+                Mention::Passable => counts[2] += 1,
+                Mention::Fair => counts[3] += 1,
+                Mention::Good => counts[4] += 1,
+                Mention::VeryGood => counts[5] += 1,
+                Mention::Excellent => counts[6] += 1,
+            }
+            total = total.saturating_add(1);
+        }
+
+        let summary = MjSummary {
+            option_id: opt.id,
+            vote_id,
+            total,
+            bad: counts[0],
+            inadequate: counts[1],
+            passable: counts[2],
+            fair: counts[3],
+            good: counts[4],
+            very_good: counts[5],
+            excellent: counts[6],
+        };
+        if ctx.db.mj_summary().option_id().find(opt.id).is_some() {
+            ctx.db.mj_summary().option_id().update(summary);
+        } else {
+            ctx.db.mj_summary().insert(summary);
+        }
+
+        gauges.push((opt.id, majority_gauge(&counts, total), counts));
+    }
+
+    recompute_mj_ranking(ctx, vote_id, gauges);
+}
+
+/// Per-option majority gauges folding in `delegated_voters`' mentions on top
+/// of each option's direct judgment ballots, for use at close/outcome time
+/// only (see the comment on `recompute_mj_summary_for_vote` for why this
+/// can't happen in the live `mj_summary`/`mj_ranking` tables themselves).
+/// `vote::finalize_vote` dense-ranks this via `dense_rank_options` to find
+/// the real median-based MJ winner, instead of the old ordinal-average
+/// `mj_score` heuristic.
+pub(crate) fn effective_mj_gauges(
+    ctx: &ReducerContext,
+    vote_id: u32,
+    delegated_voters: &[Identity],
+) -> Vec<(u32, Option<(Mention, u32, u32)>, [u32; 7])> {
+    get_vote_options(ctx, vote_id)
+        .map(|opt| {
+            let mut counts = [0u32; 7];
+            for j in ctx.db.judgment().by_option().filter(opt.id) {
+                counts[mention_to_index(j.mention)] += 1;
+            }
+            for voter in delegated_voters {
+                if let Some(j) = ctx.db.judgment().by_option_and_user().filter((opt.id, *voter)).next() {
+                    counts[mention_to_index(j.mention)] += 1;
+                }
+            }
+            let total: u32 = counts.iter().sum();
+            (opt.id, majority_gauge(&counts, total), counts)
+        })
+        .collect()
+}
+// Note: SpacetimeDB reducers cannot return data directly.
+// Private tables are not accessible via client subscriptions.
+// We need to use optimistic UI updates and server-side validation.
+
+#[spacetimedb::reducer]
+pub fn cast_judgment(ctx: &ReducerContext, option_id: u32, mention: Mention) -> Result<(), String> {
+    // 1. Find the vote option
+    let Some(option) = find_vote_option_by_id(ctx, option_id) else {
+        return Err("Vote option not found".into());
+    };
+
+    // 2. Find the parent vote and check its type
+    let Some(vote) = find_vote_by_id(ctx, option.vote_id) else {
+        // This should not happen if the option exists, but as a safeguard:
+        return Err("Parent vote not found".into());
+    };
+    if vote.voting_system != VotingSystem::MajorityJudgment {
+        return Err("This vote does not use majority judgment".into());
+    }
+    if !is_voter_authorized(ctx, &vote, ctx.sender) {
+        return Err("Not authorized to vote in this vote".into());
+    }
+    if is_vote_closed(ctx, &vote) {
+        return Err("This vote is closed".into());
+    }
+
+    // 3. Check if this is the user's first judgment for this entire vote.
+    let existing_judgments_for_vote: Vec<Judgment> = get_vote_options(ctx, vote.id)
+        .flat_map(|opt| ctx.db.judgment().by_option().filter(opt.id).filter(|j| j.voter == ctx.sender))
+        .collect();
+
+    if existing_judgments_for_vote.is_empty() {
+        // This is the first time the user is judging any option in this vote.
+        // Default all options to `Bad`.
+        for opt in get_vote_options(ctx, vote.id) {
+            ctx.db.judgment().insert(Judgment {
+                id: 0,
+                option_id: opt.id,
+                voter: ctx.sender,
+                mention: Mention::Bad, // Default mention
+            });
+            crate::vote::append_ballot_event(
+                ctx, vote.id, ctx.sender, crate::vote::BallotEventKind::Cast, Some(opt.id), format!("{:?}", Mention::Bad),
+            );
+        }
+        // Recompute summaries for the entire vote (ensures correct tie semantics)
+        recompute_mj_summary_for_vote(ctx, vote.id);
+    }
+
+    // 4. Now, insert or update the specific judgment the user just cast.
+    if let Some(existing_judgment) = ctx.db.judgment().by_option().filter(option_id).filter(|j| j.voter == ctx.sender).next() {
+        // An entry for this specific option already exists (likely just created with Bad).
+        // Update it with the user's actual mention.
+        if existing_judgment.mention != mention {
+            ctx.db.judgment().id().update(Judgment {
+                mention,
+                ..existing_judgment
+            });
+            crate::vote::append_ballot_event(
+                ctx, vote.id, ctx.sender, crate::vote::BallotEventKind::Change, Some(option_id), format!("{:?}", mention),
+            );
+        }
+    } else {
+        // This case should not be reached if the logic above is correct, but as a safeguard:
+        ctx.db.judgment().insert(Judgment {
+            id: 0,
+            option_id,
+            voter: ctx.sender,
+            mention,
+        });
+        crate::vote::append_ballot_event(
+            ctx, vote.id, ctx.sender, crate::vote::BallotEventKind::Cast, Some(option_id), format!("{:?}", mention),
+        );
+    }
+
+    // Recompute summaries for the entire vote (ensures correct tie semantics).
+    recompute_mj_summary_for_vote(ctx, option.vote_id);
+    // A direct ballot can override an outgoing delegation via
+    // self-representation, which `delegation_weight` must reflect
+    // immediately rather than only on the next delegation change.
+    crate::vote::recompute_delegation_weights(ctx, option.vote_id);
+
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+pub fn withdraw_judgments(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    // Ensure vote exists and is Majority Judgment
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.voting_system != VotingSystem::MajorityJudgment {
+        return Err("This vote does not use majority judgment".into());
+    }
+    if !is_voter_authorized(ctx, &vote, ctx.sender) {
+        return Err("Not authorized to vote in this vote".into());
+    }
+    if is_vote_closed(ctx, &vote) {
+        return Err("This vote is closed".into());
+    }
+
+    // For each option of the vote, delete the caller's judgment(s)
+    for opt in get_vote_options(ctx, vote_id) {
+        // remove all rows for this voter on this option (normally at most one)
+        let rows: Vec<_> = ctx
+            .db
+            .judgment()
+            .by_option()
+            .filter(opt.id)
+            .filter(|j| j.voter == ctx.sender)
+            .collect();
+        for r in rows {
+            ctx.db.judgment().delete(r);
+        }
+    }
+    crate::vote::append_ballot_event(ctx, vote_id, ctx.sender, crate::vote::BallotEventKind::Withdraw, None, String::new());
+
+    // Recompute summaries for the vote after changes.
+    recompute_mj_summary_for_vote(ctx, vote_id);
+    // Withdrawing the voter's direct ballot can revert them to an outgoing
+    // delegation - keep `delegation_weight` in sync too.
+    crate::vote::recompute_delegation_weights(ctx, vote_id);
+
+    Ok(())
+}
+
+// ================================
+// Clear Ballot Terminology Aliases
+// ================================
+
+/// Submit a judgment ballot for a specific option (clearer alias for cast_judgment)
+#[spacetimedb::reducer]
+pub fn submit_judgment_ballot(ctx: &ReducerContext, option_id: u32, mention: Mention) -> Result<(), String> {
+    cast_judgment(ctx, option_id, mention)
+}
+
+/// Submit a complete judgment ballot for all options in a vote in one transaction.
+/// This ensures atomicity and validates that all options have been judged.
+#[spacetimedb::reducer]
+pub fn submit_complete_judgment_ballot(
+    ctx: &ReducerContext,
+    vote_id: u32,
+    judgments: Vec<JudgmentEntry>
+) -> Result<(), String> {
+    // 1. Validate the vote exists and is Majority Judgment
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.voting_system != VotingSystem::MajorityJudgment {
+        return Err("This vote does not use majority judgment".into());
+    }
+    if !is_voter_authorized(ctx, &vote, ctx.sender) {
+        return Err("Not authorized to vote in this vote".into());
+    }
+    if is_vote_closed(ctx, &vote) {
+        return Err("This vote is closed".into());
+    }
+
+    // 2. Get all options for this vote and validate completeness
+    let options: Vec<_> = get_vote_options(ctx, vote_id).collect();
+    if judgments.len() != options.len() {
+        return Err(format!(
+            "Incomplete ballot: expected {} judgments but received {}",
+            options.len(),
+            judgments.len()
+        ));
+    }
+
+    // 3. Validate all option_ids belong to this vote
+    for entry in &judgments {
+        let valid = options.iter().any(|opt| opt.id == entry.option_id);
+        if !valid {
+            return Err(format!("Option {} does not belong to vote {}", entry.option_id, vote_id));
+        }
+    }
+
+    // 4. Check for duplicate option_ids
+    let mut seen_ids = std::collections::HashSet::new();
+    for entry in &judgments {
+        if !seen_ids.insert(entry.option_id) {
+            return Err(format!("Duplicate judgment for option {}", entry.option_id));
+        }
+    }
+
+    // 5. Delete all existing judgments for this voter on this vote (if any)
+    for opt in &options {
+        let rows: Vec<_> = ctx
+            .db
+            .judgment()
+            .by_option()
+            .filter(opt.id)
+            .filter(|j| j.voter == ctx.sender)
+            .collect();
+        for r in rows {
+            let option_id = r.option_id;
+            ctx.db.judgment().delete(r);
+            crate::vote::append_ballot_event(
+                ctx,
+                vote_id,
+                ctx.sender,
+                crate::vote::BallotEventKind::Withdraw,
+                Some(option_id),
+                String::new(),
+            );
+        }
+    }
+
+    // 6. Insert all new judgments in one transaction
+    for entry in judgments {
+        ctx.db.judgment().insert(Judgment {
+            id: 0,
+            option_id: entry.option_id,
+            voter: ctx.sender,
+            mention: entry.mention,
+        });
+        crate::vote::append_ballot_event(
+            ctx,
+            vote_id,
+            ctx.sender,
+            crate::vote::BallotEventKind::Cast,
+            Some(entry.option_id),
+            format!("{:?}", entry.mention),
+        );
+    }
+
+    // 7. Recompute summaries, and delegation weights, once for the entire vote
+    recompute_mj_summary_for_vote(ctx, vote_id);
+    crate::vote::recompute_delegation_weights(ctx, vote_id);
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+#![allow(unused_imports)]
+
+pub mod utils;
+pub mod vote;
+pub mod approval;
+pub mod judgment;
+pub mod auth_validation;
+pub mod vote_access;
+pub mod sealed_ballot;
+pub mod stv;
+pub mod tie_break;
+
+pub use approval::{
+    Approval,
+    // Original names
+    approve, unapprove, set_approvals,
+    // Clear ballot terminology aliases
+    submit_approval_ballot, withdraw_approval_ballot, set_approval_ballot
+};
+pub use judgment::{
+    Mention, Judgment, JudgmentEntry,
+    MjRanking,
+    // Original name
+    cast_judgment,
+    // Clear ballot terminology aliases
+    submit_judgment_ballot, submit_complete_judgment_ballot
+};
+pub use vote::{
+    Vote,
+    VoteOption,
+    VoteStatus,
+    ServerInfo,
+    create_vote,
+    delete_vote,
+    close_vote,
+    finalize_vote_on_schedule,
+    ensure_server_info,
+    VoteDelegation, DelegationWeight,
+    set_delegation, clear_delegation,
+    // Clear ballot terminology aliases
+    delegate_vote, undelegate_vote,
+    VoteOutcome,
+    compute_outcome,
+    BallotEvent, BallotEventKind,
+    finalize_audit,
+    MAX_OPTIONS,
+};
+pub use auth_validation::{
+    JwtClaims,
+    authenticated_create_vote,
+};
+pub use vote_access::{
+    VoteAccess,
+    grant_access_by_token, grant_access_to_user, revoke_access,
+};
+pub use sealed_ballot::{
+    BallotPhase, SealedBallot, BallotCommitment,
+    commit_ballot, reveal_ballot, open_reveal_phase,
+};
+pub use stv::{
+    SurplusMethod, RankedBallot, StvResult,
+    cast_ranked_ballot, submit_complete_ranked_ballot, withdraw_ranked_ballot,
+};
+pub use tie_break::TieStrategy;
@@ -0,0 +1,197 @@
+use spacetimedb::{ReducerContext, SpacetimeType, Table, Identity};
+
+use crate::vote::{find_vote_by_id, VotingSystem};
+use crate::judgment::JudgmentEntry;
+
+/// Lifecycle of a sealed (commit-reveal) vote. `Vote::phase` is `None` for
+/// votes that don't use sealed ballots at all.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BallotPhase {
+    Commit,
+    Reveal,
+    Closed,
+}
+
+/// A plaintext ballot as revealed by its voter, shaped to match whichever
+/// voting system the vote uses.
+#[derive(SpacetimeType, Clone, Debug)]
+pub enum SealedBallot {
+    Approval(Vec<u32>),
+    Judgment(Vec<JudgmentEntry>),
+}
+
+// One row per (vote_id, voter): the commitment submitted during the Commit
+// phase, and whether it has since been revealed. Not public - the whole
+// point is that nobody (including other clients) can see the plaintext
+// ballot, or even who has committed, before the Reveal phase.
+#[spacetimedb::table(
+    name = ballot_commitment,
+    index(name = by_vote, btree(columns = [vote_id])),
+    index(name = by_vote_and_voter, btree(columns = [vote_id, voter]))
+)]
+pub struct BallotCommitment {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub voter: Identity,
+    /// blake3(canonical_ballot || nonce), 32 bytes.
+    pub digest: Vec<u8>,
+    pub revealed: bool,
+}
+
+fn find_commitment(ctx: &ReducerContext, vote_id: u32, voter: Identity) -> Option<BallotCommitment> {
+    ctx.db.ballot_commitment().by_vote_and_voter().filter((vote_id, voter)).next()
+}
+
+/// Deterministically encode a ballot so every client computes the same
+/// commitment digest for the same choices, regardless of submission order.
+fn canonical_encoding(ballot: &SealedBallot) -> Vec<u8> {
+    match ballot {
+        SealedBallot::Approval(option_ids) => {
+            let mut sorted = option_ids.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            let mut bytes = Vec::with_capacity(sorted.len() * 4);
+            for id in sorted {
+                bytes.extend_from_slice(&id.to_be_bytes());
+            }
+            bytes
+        }
+        SealedBallot::Judgment(entries) => {
+            let mut sorted = entries.clone();
+            sorted.sort_by_key(|e| e.option_id);
+            let mut bytes = Vec::with_capacity(sorted.len() * 5);
+            for entry in sorted {
+                bytes.extend_from_slice(&entry.option_id.to_be_bytes());
+                bytes.push(entry.mention as u8);
+            }
+            bytes
+        }
+    }
+}
+
+/// Submit a commitment for the caller's ballot in a sealed vote. Replaces
+/// any prior (unrevealed) commitment for this voter.
+#[spacetimedb::reducer]
+pub fn commit_ballot(ctx: &ReducerContext, vote_id: u32, commitment: Vec<u8>) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if !vote.sealed {
+        return Err("This vote does not use sealed (commit-reveal) ballots".into());
+    }
+    if vote.phase != Some(BallotPhase::Commit) {
+        return Err("This vote is not in its commit phase".into());
+    }
+    if commitment.len() != 32 {
+        return Err("Commitment must be a 32-byte digest".into());
+    }
+
+    if let Some(existing) = find_commitment(ctx, vote_id, ctx.sender) {
+        if existing.revealed {
+            return Err("Ballot already revealed; cannot change commitment".into());
+        }
+        ctx.db.ballot_commitment().id().update(BallotCommitment {
+            digest: commitment,
+            ..existing
+        });
+    } else {
+        ctx.db.ballot_commitment().insert(BallotCommitment {
+            id: 0,
+            vote_id,
+            voter: ctx.sender,
+            digest: commitment,
+            revealed: false,
+        });
+    }
+    Ok(())
+}
+
+/// Reveal the caller's plaintext ballot and nonce, verify it against the
+/// stored commitment, and - only once verified - apply it to the normal
+/// `approval`/`judgment` tables.
+#[spacetimedb::reducer]
+pub fn reveal_ballot(ctx: &ReducerContext, vote_id: u32, ballot: SealedBallot, nonce: Vec<u8>) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if !vote.sealed {
+        return Err("This vote does not use sealed (commit-reveal) ballots".into());
+    }
+    if vote.phase != Some(BallotPhase::Reveal) {
+        return Err("This vote is not in its reveal phase".into());
+    }
+    if nonce.len() < 16 {
+        return Err("Nonce must be at least 16 bytes".into());
+    }
+    match (&vote.voting_system, &ballot) {
+        (VotingSystem::Approval, SealedBallot::Approval(_)) => {}
+        (VotingSystem::MajorityJudgment, SealedBallot::Judgment(_)) => {}
+        _ => return Err("Ballot shape does not match this vote's voting system".into()),
+    }
+
+    let Some(commitment) = find_commitment(ctx, vote_id, ctx.sender) else {
+        return Err("No commitment found for this voter".into());
+    };
+    if commitment.revealed {
+        return Err("Ballot already revealed".into());
+    }
+
+    let mut preimage = canonical_encoding(&ballot);
+    preimage.extend_from_slice(&nonce);
+    let digest = blake3::hash(&preimage);
+    if digest.as_bytes().as_slice() != commitment.digest.as_slice() {
+        return Err("Revealed ballot does not match the stored commitment".into());
+    }
+
+    ctx.db.ballot_commitment().id().update(BallotCommitment {
+        revealed: true,
+        ..commitment
+    });
+
+    match ballot {
+        SealedBallot::Approval(option_ids) => crate::approval::set_approvals(ctx, vote_id, option_ids),
+        SealedBallot::Judgment(judgments) => crate::judgment::submit_complete_judgment_ballot(ctx, vote_id, judgments),
+    }
+}
+
+/// Delete any commitment that was never revealed. Called when a sealed
+/// vote closes - an unrevealed ballot simply never counts.
+pub(crate) fn purge_unrevealed_commitments(ctx: &ReducerContext, vote_id: u32) {
+    for row in ctx.db.ballot_commitment().by_vote().filter(vote_id) {
+        if !row.revealed {
+            ctx.db.ballot_commitment().delete(row);
+        }
+    }
+}
+
+/// Delete every commitment row for a vote, revealed or not. Used by `delete_vote`.
+pub(crate) fn delete_commitments_for_vote(ctx: &ReducerContext, vote_id: u32) {
+    for row in ctx.db.ballot_commitment().by_vote().filter(vote_id) {
+        ctx.db.ballot_commitment().delete(row);
+    }
+}
+
+/// Advance a sealed vote from Commit to Reveal. Creator-only.
+#[spacetimedb::reducer]
+pub fn open_reveal_phase(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.creator != ctx.sender {
+        return Err("Only the vote creator can advance the ballot phase".into());
+    }
+    if !vote.sealed {
+        return Err("This vote does not use sealed (commit-reveal) ballots".into());
+    }
+    if vote.phase != Some(BallotPhase::Commit) {
+        return Err("This vote is not in its commit phase".into());
+    }
+
+    ctx.db.vote().id().update(crate::vote::Vote {
+        phase: Some(BallotPhase::Reveal),
+        ..vote
+    });
+    Ok(())
+}
@@ -0,0 +1,674 @@
+use spacetimedb::{ReducerContext, SpacetimeType, Table, Identity, Filter, client_visibility_filter};
+use std::collections::{HashMap, HashSet};
+
+use crate::vote::{find_vote_by_id, get_vote_options, is_voter_authorized, is_vote_closed, VotingSystem, Vote};
+use crate::tie_break::{TieStrategy, resolve_tie};
+
+/// Which ballots move, and how their transfer value is recomputed, once a
+/// candidate passes the Droop quota. See `transfer_surplus`.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SurplusMethod {
+    /// Every ballot currently held by the elected candidate transfers,
+    /// each scaled by `surplus / total_held` (its own current value).
+    WeightedInclusiveGregory,
+    /// Every ballot currently held transfers at an equal share of the
+    /// surplus, ignoring each ballot's current transfer value.
+    UnweightedInclusiveGregory,
+    /// Only the parcel of ballots most recently transferred to the
+    /// candidate (or their original first preferences, if the candidate
+    /// was never the target of an earlier transfer) moves.
+    ExclusiveGregory,
+    /// Meek method: instead of freezing transfer values at the moment a
+    /// candidate is elected, every elected candidate keeps a continuously
+    /// adjusted "keep factor" and the whole count is redistributed from
+    /// scratch each iteration until all elected candidates converge on the
+    /// quota. See `compute_meek`.
+    Meek,
+}
+
+const DEFAULT_DECIMAL_PLACES: u8 = 4;
+
+fn scale_for(vote: &Vote) -> i64 {
+    10i64.pow(vote.stv_decimal_places.unwrap_or(DEFAULT_DECIMAL_PLACES) as u32)
+}
+
+/// One ranked-choice ballot. `preferences` may be a partial ranking -
+/// options the voter never ranked are simply never reached, and the ballot
+/// exhausts once it runs out of continuing preferences.
+/// Public with RLS so each client only sees their own ballot rows.
+#[spacetimedb::table(
+    name = ranked_ballot,
+    public,
+    index(name = by_vote, btree(columns = [vote_id])),
+    index(name = by_vote_and_voter, btree(columns = [vote_id, voter]))
+)]
+pub struct RankedBallot {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub voter: Identity,
+    pub preferences: Vec<u32>,
+}
+
+// RLS: a client may only see their own ranked ballot
+#[client_visibility_filter]
+const RANKED_BALLOT_RLS: Filter = Filter::Sql(
+    "SELECT ranked_ballot.* FROM ranked_ballot WHERE ranked_ballot.voter = :sender"
+);
+
+/// Precomputed per-(option, round) snapshot of an STV count. Fully
+/// recomputed from `ranked_ballot` by `recompute_stv_for_vote` every time a
+/// ballot changes - never patched incrementally.
+#[spacetimedb::table(
+    name = stv_result,
+    public,
+    index(name = by_vote, btree(columns = [vote_id])),
+    index(name = by_vote_and_option, btree(columns = [vote_id, option_id]))
+)]
+pub struct StvResult {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub option_id: u32,
+    pub round: u32,
+    /// This option's tally at the end of `round`, fixed-point scaled by
+    /// `10^Vote::stv_decimal_places`.
+    pub votes_fp: i64,
+    pub elected: bool,
+    pub eliminated: bool,
+    /// Meek method only: the keep factor this candidate had during
+    /// `round`'s distribution (an election or keep-factor adjustment made
+    /// in `round` shows from the next round on), same fixed-point scale as
+    /// `votes_fp` (scale = 1.0, i.e. "keeps everything"). Not meaningful for the Gregory methods, which
+    /// always log `scale` here.
+    pub keep_factor_fp: i64,
+    /// Set when this option was eliminated/excluded this round as the loser
+    /// of a tie (equal lowest tally) broken by `Vote::tie_strategy`. `None`
+    /// when there was no tie to break.
+    pub tie_broken_by: Option<crate::tie_break::TieStrategy>,
+}
+
+fn encode_preferences(preferences: &[u32]) -> String {
+    preferences.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+}
+
+#[spacetimedb::reducer]
+pub fn cast_ranked_ballot(ctx: &ReducerContext, vote_id: u32, preferences: Vec<u32>) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.voting_system != VotingSystem::SingleTransferableVote {
+        return Err("This vote does not use ranked-choice (STV) voting".into());
+    }
+    if !is_voter_authorized(ctx, &vote, ctx.sender) {
+        return Err("Not authorized to vote in this vote".into());
+    }
+    if is_vote_closed(ctx, &vote) {
+        return Err("This vote is closed".into());
+    }
+
+    let options: Vec<_> = get_vote_options(ctx, vote_id).collect();
+    let mut seen = HashSet::new();
+    for &option_id in &preferences {
+        if !options.iter().any(|o| o.id == option_id) {
+            return Err(format!("Option {} does not belong to vote {}", option_id, vote_id));
+        }
+        if !seen.insert(option_id) {
+            return Err(format!("Duplicate preference for option {}", option_id));
+        }
+    }
+
+    if let Some(existing) = ctx.db.ranked_ballot().by_vote_and_voter().filter((vote_id, ctx.sender)).next() {
+        ctx.db.ranked_ballot().id().update(RankedBallot {
+            preferences: preferences.clone(),
+            ..existing
+        });
+        crate::vote::append_ballot_event(
+            ctx, vote_id, ctx.sender, crate::vote::BallotEventKind::Change, None, encode_preferences(&preferences),
+        );
+    } else {
+        ctx.db.ranked_ballot().insert(RankedBallot {
+            id: 0,
+            vote_id,
+            voter: ctx.sender,
+            preferences: preferences.clone(),
+        });
+        crate::vote::append_ballot_event(
+            ctx, vote_id, ctx.sender, crate::vote::BallotEventKind::Cast, None, encode_preferences(&preferences),
+        );
+    }
+
+    recompute_stv_for_vote(ctx, vote_id);
+    // A direct ballot can override an outgoing delegation via
+    // self-representation, which `delegation_weight` must reflect
+    // immediately rather than only on the next delegation change.
+    crate::vote::recompute_delegation_weights(ctx, vote_id);
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+pub fn withdraw_ranked_ballot(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.voting_system != VotingSystem::SingleTransferableVote {
+        return Err("This vote does not use ranked-choice (STV) voting".into());
+    }
+    if !is_voter_authorized(ctx, &vote, ctx.sender) {
+        return Err("Not authorized to vote in this vote".into());
+    }
+    if is_vote_closed(ctx, &vote) {
+        return Err("This vote is closed".into());
+    }
+
+    if let Some(existing) = ctx.db.ranked_ballot().by_vote_and_voter().filter((vote_id, ctx.sender)).next() {
+        ctx.db.ranked_ballot().delete(existing);
+        crate::vote::append_ballot_event(
+            ctx, vote_id, ctx.sender, crate::vote::BallotEventKind::Withdraw, None, String::new(),
+        );
+    }
+
+    recompute_stv_for_vote(ctx, vote_id);
+    // Withdrawing the voter's direct ballot can revert them to an outgoing
+    // delegation - keep `delegation_weight` in sync either way.
+    crate::vote::recompute_delegation_weights(ctx, vote_id);
+    Ok(())
+}
+
+// ================================
+// Clear Ballot Terminology Alias
+// ================================
+
+/// Submit a complete ranked-choice ballot (clear ballot terminology alias
+/// for `cast_ranked_ballot` - STV ballots are always submitted whole, there
+/// is no per-option variant the way `cast_judgment` has one).
+#[spacetimedb::reducer]
+pub fn submit_complete_ranked_ballot(ctx: &ReducerContext, vote_id: u32, preferences: Vec<u32>) -> Result<(), String> {
+    cast_ranked_ballot(ctx, vote_id, preferences)
+}
+
+// ================================
+// STV counting
+// ================================
+
+struct Ballot {
+    preferences: Vec<u32>,
+    pos: usize,
+    value_fp: i64,
+    /// Round this ballot's `pos` last advanced in (0 = still sitting with a
+    /// first preference). Used by `ExclusiveGregory` to isolate the most
+    /// recent transfer parcel.
+    arrived_round: u32,
+}
+
+pub(crate) struct StvRound {
+    pub option_id: u32,
+    pub round: u32,
+    pub votes_fp: i64,
+    pub elected: bool,
+    pub eliminated: bool,
+    pub keep_factor_fp: i64,
+    pub tie_broken_by: Option<TieStrategy>,
+}
+
+fn mark(log: &mut [StvRound], round: u32, option_id: u32, elected: bool, eliminated: bool) {
+    if let Some(entry) = log.iter_mut().rev().find(|r| r.round == round && r.option_id == option_id) {
+        entry.elected = elected;
+        entry.eliminated = eliminated;
+    }
+}
+
+fn mark_tie(log: &mut [StvRound], round: u32, option_id: u32, strategy: TieStrategy) {
+    if let Some(entry) = log.iter_mut().rev().find(|r| r.round == round && r.option_id == option_id) {
+        entry.tie_broken_by = Some(strategy);
+    }
+}
+
+/// Pick which of a set of candidates tied for the lowest tally is the actual
+/// loser, per `Vote::tie_strategy`: `resolve_tie` orders the tied group
+/// best-first using each candidate's tally in the previous round as the
+/// "earlier stage" to compare, so the loser is whoever sorts last.
+fn break_elimination_tie(log: &mut [StvRound], vote_id: u32, strategy: TieStrategy, round: u32, tied: &[u32]) -> u32 {
+    let prev_round = round.saturating_sub(1);
+    let order = resolve_tie(vote_id, tied, strategy, |id| {
+        log.iter().rev().find(|r| r.round == prev_round && r.option_id == id).map(|r| r.votes_fp).unwrap_or(0)
+    });
+    let loser = *order.last().expect("tied is non-empty");
+    mark_tie(log, round, loser, strategy);
+    loser
+}
+
+fn advance_to_continuing(ballot: &mut Ballot, continuing: &HashSet<u32>) {
+    while ballot.pos < ballot.preferences.len() && !continuing.contains(&ballot.preferences[ballot.pos]) {
+        ballot.pos += 1;
+    }
+}
+
+/// Distribute an elected candidate's surplus (`total - quota`) across the
+/// ballots currently sitting with them, per `method`, then advance each
+/// moved ballot's pointer to its next continuing preference.
+fn transfer_surplus(
+    ballots: &mut [Ballot],
+    elected_id: u32,
+    surplus_fp: i64,
+    total_fp: i64,
+    method: SurplusMethod,
+    continuing: &HashSet<u32>,
+    round: u32,
+) {
+    let held: Vec<usize> = ballots
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| b.pos < b.preferences.len() && b.preferences[b.pos] == elected_id)
+        .map(|(i, _)| i)
+        .collect();
+    if held.is_empty() || total_fp == 0 {
+        return;
+    }
+
+    let parcel: Vec<usize> = match method {
+        SurplusMethod::ExclusiveGregory => {
+            let latest = held.iter().map(|&i| ballots[i].arrived_round).max().unwrap_or(0);
+            held.iter().copied().filter(|&i| ballots[i].arrived_round == latest).collect()
+        }
+        SurplusMethod::WeightedInclusiveGregory | SurplusMethod::UnweightedInclusiveGregory | SurplusMethod::Meek => held.clone(),
+    };
+    if parcel.is_empty() {
+        return;
+    }
+    // Held ballots outside the parcel stay with the elected candidate: they
+    // carry no value onward once the candidate leaves the count.
+    for &i in &held {
+        if !parcel.contains(&i) {
+            ballots[i].value_fp = 0;
+        }
+    }
+
+    match method {
+        SurplusMethod::WeightedInclusiveGregory | SurplusMethod::Meek => {
+            for &i in &parcel {
+                let v = ballots[i].value_fp as i128;
+                ballots[i].value_fp = (v * surplus_fp as i128 / total_fp as i128) as i64;
+            }
+        }
+        SurplusMethod::UnweightedInclusiveGregory | SurplusMethod::ExclusiveGregory => {
+            let share = surplus_fp as i128 / parcel.len() as i128;
+            for &i in &parcel {
+                ballots[i].value_fp = share as i64;
+            }
+        }
+    }
+
+    for &i in &parcel {
+        ballots[i].pos += 1;
+        advance_to_continuing(&mut ballots[i], continuing);
+        ballots[i].arrived_round = round;
+    }
+}
+
+/// Pure computation of a full STV count for `vote_id`: the Droop quota,
+/// repeated election/elimination rounds, and the per-round tally log. Does
+/// not touch `stv_result` - see `recompute_stv_for_vote` for that.
+pub(crate) fn compute_stv_rounds(ctx: &ReducerContext, vote_id: u32) -> Vec<StvRound> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Vec::new();
+    };
+    let options: Vec<_> = get_vote_options(ctx, vote_id).collect();
+    let option_ids: HashSet<u32> = options.iter().map(|o| o.id).collect();
+    if option_ids.is_empty() {
+        return Vec::new();
+    }
+    let scale = scale_for(&vote);
+    let seats = vote.stv_seats.unwrap_or(1).max(1).min(options.len() as u32);
+    let method = vote.stv_surplus_method.unwrap_or(SurplusMethod::WeightedInclusiveGregory);
+
+    if method == SurplusMethod::Meek {
+        return compute_meek(ctx, &vote, &options, scale, seats);
+    }
+
+    let mut ballots: Vec<Ballot> = ctx
+        .db
+        .ranked_ballot()
+        .by_vote()
+        .filter(vote_id)
+        .map(|b| Ballot {
+            preferences: b.preferences.into_iter().filter(|id| option_ids.contains(id)).collect(),
+            pos: 0,
+            value_fp: scale,
+            arrived_round: 0,
+        })
+        .collect();
+
+    let valid_ballots = ballots.iter().filter(|b| !b.preferences.is_empty()).count() as i64;
+    let quota_fp = (valid_ballots / (seats as i64 + 1) + 1) * scale;
+
+    let mut continuing: HashSet<u32> = option_ids.clone();
+    let mut elected: Vec<u32> = Vec::new();
+    let mut log: Vec<StvRound> = Vec::new();
+    let mut round: u32 = 0;
+    // Safety cap so a bug can never spin forever - there are at most
+    // options.len() eliminations plus seats elections before the race ends.
+    let max_rounds = options.len() as u32 + 2;
+
+    while elected.len() < seats as usize && round < max_rounds {
+        round += 1;
+
+        for b in &mut ballots {
+            advance_to_continuing(b, &continuing);
+        }
+
+        let mut tallies: Vec<(u32, i64)> = continuing
+            .iter()
+            .map(|&id| {
+                let sum: i64 = ballots
+                    .iter()
+                    .filter(|b| b.pos < b.preferences.len() && b.preferences[b.pos] == id)
+                    .map(|b| b.value_fp)
+                    .sum();
+                (id, sum)
+            })
+            .collect();
+        tallies.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        for &(id, total) in &tallies {
+            log.push(StvRound { option_id: id, round, votes_fp: total, elected: false, eliminated: false, keep_factor_fp: scale, tie_broken_by: None });
+        }
+
+        // If the remaining seats equal the remaining candidates, the race is
+        // decided - elect everyone left, highest tally first.
+        let remaining_seats = seats as usize - elected.len();
+        if continuing.len() <= remaining_seats {
+            for &(id, _) in &tallies {
+                continuing.remove(&id);
+                elected.push(id);
+                mark(&mut log, round, id, true, false);
+            }
+            break;
+        }
+
+        let reaching: Vec<(u32, i64)> = tallies.iter().copied().filter(|(_, t)| *t >= quota_fp).collect();
+        if !reaching.is_empty() {
+            for (id, total) in reaching {
+                if elected.len() >= seats as usize {
+                    break;
+                }
+                continuing.remove(&id);
+                elected.push(id);
+                mark(&mut log, round, id, true, false);
+                // A zero surplus still goes through `transfer_surplus`, so the
+                // candidate's ballots move on at zero value.
+                let surplus = total - quota_fp;
+                transfer_surplus(&mut ballots, id, surplus, total, method, &continuing, round);
+            }
+            continue;
+        }
+
+        // Nobody reached quota: eliminate the lowest, transferring their
+        // ballots onward at full current value. If several tie for lowest,
+        // `Vote::tie_strategy` decides who actually goes.
+        if let Some(&(_, min_tally)) = tallies.last() {
+            let tied: Vec<u32> = tallies.iter().filter(|&&(_, t)| t == min_tally).map(|&(id, _)| id).collect();
+            let low_id = if tied.len() > 1 {
+                let strategy = vote.tie_strategy.unwrap_or(TieStrategy::Forwards);
+                break_elimination_tie(&mut log, vote_id, strategy, round, &tied)
+            } else {
+                tied[0]
+            };
+            continuing.remove(&low_id);
+            mark(&mut log, round, low_id, false, true);
+            for b in &mut ballots {
+                if b.pos < b.preferences.len() && b.preferences[b.pos] == low_id {
+                    b.pos += 1;
+                    advance_to_continuing(b, &continuing);
+                    b.arrived_round = round;
+                }
+            }
+        } else {
+            break;
+        }
+    }
+
+    log
+}
+
+/// One iteration of Meek weight distribution: walk every ballot's
+/// preference list, letting elected candidates keep `keep_factor` of the
+/// weight that reaches them and pass the rest on, skipping excluded
+/// candidates entirely, and letting the first continuing (hopeful)
+/// candidate reached take the whole remaining weight. Returns each
+/// candidate's received weight plus the total weight that ran off the end
+/// of a ballot's list unclaimed (exhausted).
+fn distribute_meek(
+    ballots: &[Vec<u32>],
+    scale: i64,
+    elected: &HashSet<u32>,
+    excluded: &HashSet<u32>,
+    keep_factor: &HashMap<u32, i64>,
+) -> (HashMap<u32, i64>, i64) {
+    let mut received: HashMap<u32, i64> = HashMap::new();
+    let mut exhausted_total: i64 = 0;
+    for prefs in ballots {
+        let mut weight: i64 = scale;
+        for &cand in prefs {
+            if weight <= 0 {
+                break;
+            }
+            if excluded.contains(&cand) {
+                continue;
+            }
+            if elected.contains(&cand) {
+                let k = *keep_factor.get(&cand).unwrap_or(&scale);
+                let kept = (weight as i128 * k as i128 / scale as i128) as i64;
+                *received.entry(cand).or_insert(0) += kept;
+                weight -= kept;
+                continue;
+            }
+            // First hopeful reached: takes everything left on this ballot.
+            *received.entry(cand).or_insert(0) += weight;
+            weight = 0;
+            break;
+        }
+        exhausted_total += weight;
+    }
+    (received, exhausted_total)
+}
+
+/// Meek-method count: candidates keep a continuously adjusted keep factor
+/// instead of freezing transfer values at the moment of election, and the
+/// whole ballot set is redistributed from scratch every iteration. A
+/// candidate's keep factor only changes once per iteration (`keep_factor
+/// *= quota / received`), and we treat "within `tolerance_fp` of the quota"
+/// as converged - `tolerance_fp` is tied to `Vote::stv_decimal_places`
+/// (the same knob that sets fixed-point precision) rather than a separate
+/// field, since tightening precision already tightens the practical
+/// tolerance.
+fn compute_meek(
+    ctx: &ReducerContext,
+    vote: &Vote,
+    options: &[crate::vote::VoteOption],
+    scale: i64,
+    seats: u32,
+) -> Vec<StvRound> {
+    let option_ids: HashSet<u32> = options.iter().map(|o| o.id).collect();
+    let tolerance_fp: i64 = 1;
+
+    let ballots: Vec<Vec<u32>> = ctx
+        .db
+        .ranked_ballot()
+        .by_vote()
+        .filter(vote.id)
+        .map(|b| b.preferences.into_iter().filter(|id| option_ids.contains(id)).collect::<Vec<_>>())
+        .collect();
+
+    let mut excluded: HashSet<u32> = HashSet::new();
+    let mut elected_set: HashSet<u32> = HashSet::new();
+    let mut keep_factor: HashMap<u32, i64> = HashMap::new();
+    let mut log: Vec<StvRound> = Vec::new();
+    let mut round: u32 = 0;
+    // Generous cap: each election/exclusion can take several convergence
+    // iterations, but the process is still bounded by the candidate count.
+    let max_rounds = (options.len() as u32 + 1) * 20 + 20;
+
+    while elected_set.len() < seats as usize && round < max_rounds {
+        round += 1;
+
+        let (received, exhausted) = distribute_meek(&ballots, scale, &elected_set, &excluded, &keep_factor);
+        let total_fp = ballots.len() as i64 * scale;
+        let active_fp = total_fp - exhausted;
+        let quota_fp = active_fp / (seats as i64 + 1);
+
+        let hopefuls: Vec<u32> = option_ids
+            .iter()
+            .copied()
+            .filter(|id| !excluded.contains(id) && !elected_set.contains(id))
+            .collect();
+
+        for &id in option_ids.iter() {
+            if excluded.contains(&id) {
+                continue;
+            }
+            let r = *received.get(&id).unwrap_or(&0);
+            let k = if elected_set.contains(&id) { *keep_factor.get(&id).unwrap_or(&scale) } else { scale };
+            log.push(StvRound { option_id: id, round, votes_fp: r, elected: elected_set.contains(&id), eliminated: false, keep_factor_fp: k, tie_broken_by: None });
+        }
+
+        let remaining_seats = seats as usize - elected_set.len();
+        if hopefuls.len() <= remaining_seats {
+            let mut ranked = hopefuls.clone();
+            ranked.sort_unstable_by(|a, b| {
+                received.get(b).unwrap_or(&0).cmp(received.get(a).unwrap_or(&0)).then(a.cmp(b))
+            });
+            for id in ranked {
+                elected_set.insert(id);
+                keep_factor.insert(id, scale);
+                mark(&mut log, round, id, true, false);
+            }
+            break;
+        }
+
+        let mut newly_reaching: Vec<(u32, i64)> = hopefuls
+            .iter()
+            .filter_map(|&id| received.get(&id).map(|&r| (id, r)))
+            .filter(|(_, r)| *r >= quota_fp)
+            .collect();
+        newly_reaching.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        if let Some(&(id, r)) = newly_reaching.first() {
+            let k = if r > 0 {
+                ((quota_fp as i128 * scale as i128 / r as i128).min(scale as i128).max(0)) as i64
+            } else {
+                scale
+            };
+            elected_set.insert(id);
+            keep_factor.insert(id, k);
+            mark(&mut log, round, id, true, false);
+            continue;
+        }
+
+        let converged = elected_set.iter().all(|id| {
+            let r = *received.get(id).unwrap_or(&0);
+            (r - quota_fp).abs() <= tolerance_fp
+        });
+
+        if !elected_set.is_empty() && !converged {
+            let candidates: Vec<u32> = elected_set.iter().copied().collect();
+            for id in candidates {
+                let r = *received.get(&id).unwrap_or(&0);
+                if r > 0 {
+                    let old_k = *keep_factor.get(&id).unwrap_or(&scale);
+                    let new_k = ((old_k as i128 * quota_fp as i128 / r as i128).min(scale as i128).max(0)) as i64;
+                    keep_factor.insert(id, new_k);
+                }
+            }
+            continue;
+        }
+
+        // Elected candidates (if any) have converged and nobody new reached
+        // quota: exclude the hopeful with the fewest votes. If several tie
+        // for fewest, `Vote::tie_strategy` decides who actually goes.
+        let mut ranked_hopefuls: Vec<(u32, i64)> =
+            hopefuls.iter().map(|&id| (id, *received.get(&id).unwrap_or(&0))).collect();
+        ranked_hopefuls.sort_unstable_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+        if let Some(&(_, min_received)) = ranked_hopefuls.first() {
+            let tied: Vec<u32> = ranked_hopefuls.iter().filter(|&&(_, r)| r == min_received).map(|&(id, _)| id).collect();
+            let low_id = if tied.len() > 1 {
+                let strategy = vote.tie_strategy.unwrap_or(TieStrategy::Forwards);
+                break_elimination_tie(&mut log, vote.id, strategy, round, &tied)
+            } else {
+                tied[0]
+            };
+            excluded.insert(low_id);
+            mark(&mut log, round, low_id, false, true);
+        } else {
+            break;
+        }
+    }
+
+    log
+}
+
+/// Recompute and store the `stv_result` snapshot for `vote_id`. Safe to call
+/// any time a ranked ballot changes, or when a vote closes.
+pub(crate) fn recompute_stv_for_vote(ctx: &ReducerContext, vote_id: u32) {
+    for row in ctx.db.stv_result().by_vote().filter(vote_id) {
+        ctx.db.stv_result().delete(row);
+    }
+    for r in compute_stv_rounds(ctx, vote_id) {
+        ctx.db.stv_result().insert(StvResult {
+            id: 0,
+            vote_id,
+            option_id: r.option_id,
+            round: r.round,
+            votes_fp: r.votes_fp,
+            elected: r.elected,
+            eliminated: r.eliminated,
+            keep_factor_fp: r.keep_factor_fp,
+            tie_broken_by: r.tie_broken_by,
+        });
+    }
+}
+
+/// Whether `option_id` was ever marked elected in `vote_id`'s stored
+/// `stv_result` log.
+pub(crate) fn is_elected(ctx: &ReducerContext, vote_id: u32, option_id: u32) -> bool {
+    ctx.db
+        .stv_result()
+        .by_vote_and_option()
+        .filter((vote_id, option_id))
+        .any(|r| r.elected)
+}
+
+/// STV has no incremental counter the way approval/MJ have - `stv_result` is
+/// always a full recompute from `ranked_ballot`. So the "audit" here is
+/// recomputing from scratch and comparing against what's currently stored,
+/// rather than replaying `ballot_event` (a mismatch would mean
+/// `recompute_stv_for_vote` was not called after a ballot change, which
+/// would itself be a bug).
+pub(crate) fn audit_stv(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    let recomputed = compute_stv_rounds(ctx, vote_id);
+    let mut stored: Vec<_> = ctx.db.stv_result().by_vote().filter(vote_id).collect();
+    stored.sort_unstable_by(|a, b| a.round.cmp(&b.round).then(a.option_id.cmp(&b.option_id)));
+    let mut expected: Vec<_> = recomputed.iter().collect();
+    expected.sort_unstable_by(|a, b| a.round.cmp(&b.round).then(a.option_id.cmp(&b.option_id)));
+
+    if stored.len() != expected.len() {
+        return Err(format!(
+            "Audit mismatch for vote {}: stored stv_result has {} rows but recomputing from ranked_ballot yields {}",
+            vote_id, stored.len(), expected.len()
+        ));
+    }
+    for (s, e) in stored.iter().zip(expected.iter()) {
+        if s.option_id != e.option_id || s.round != e.round || s.votes_fp != e.votes_fp
+            || s.elected != e.elected || s.eliminated != e.eliminated
+            || s.keep_factor_fp != e.keep_factor_fp || s.tie_broken_by != e.tie_broken_by
+        {
+            return Err(format!(
+                "Audit mismatch for vote {} option {} round {}: stored result does not match recomputation",
+                vote_id, s.option_id, s.round
+            ));
+        }
+    }
+    Ok(())
+}
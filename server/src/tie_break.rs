@@ -0,0 +1,78 @@
+/// Deterministic, configurable tie-breaking shared by Majority Judgment
+/// ranking (`crate::judgment`) and STV elimination/exclusion
+/// (`crate::stv`).
+///
+/// This module needs (for the `Random` strategy's hashing), in Cargo.toml:
+/// ```toml
+/// [dependencies]
+/// sha2 = "0.10"
+/// ```
+
+use spacetimedb::SpacetimeType;
+use sha2::{Digest, Sha256};
+
+/// How a tie between options with otherwise equal standing is resolved.
+/// Stored per vote so every client - and a reducer replay - agree on the
+/// outcome.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TieStrategy {
+    /// Prefer whichever tied option was ahead at the earlier stage compared.
+    Forwards,
+    /// Prefer whichever tied option was behind at the earlier stage compared.
+    Backwards,
+    /// Shuffle the tied options with a deterministic SHA-256-seeded stream.
+    /// Reproducible across reducer replays, since the seed is derived only
+    /// from stable, vote-scoped data - never wall-clock time or `ctx`.
+    Random,
+}
+
+/// Byte stream for `Random` tie-breaking: repeatedly hashes `seed` with an
+/// incrementing counter appended, yielding the bytes of each digest in turn.
+/// Deterministic by construction, so the same seed always produces the same
+/// stream.
+fn deterministic_stream(seed: String) -> impl Iterator<Item = u8> {
+    let mut counter: u32 = 0;
+    std::iter::from_fn(move || {
+        let mut hasher = Sha256::new();
+        hasher.update(seed.as_bytes());
+        hasher.update(counter.to_be_bytes());
+        counter = counter.wrapping_add(1);
+        Some(hasher.finalize().to_vec())
+    })
+    .flatten()
+}
+
+/// Fisher-Yates shuffle of `items`, driven by `deterministic_stream(seed)`.
+fn deterministic_shuffle<T>(items: &mut [T], seed: String) {
+    let mut stream = deterministic_stream(seed);
+    for i in (1..items.len()).rev() {
+        let byte = stream.next().unwrap_or(0);
+        let j = (byte as usize) % (i + 1);
+        items.swap(i, j);
+    }
+}
+
+/// Order a tied group of option ids best-first per `strategy`: the option at
+/// index 0 is the one that "wins" the tie.
+///
+/// `earlier_stage` gives each option's standing at the stage being compared
+/// (for MJ ranking, the count at the next-higher grade; for STV, the
+/// previous round's tally). `Forwards` prefers the higher value, `Backwards`
+/// the lower. `Random` ignores `earlier_stage` and instead shuffles using a
+/// seed derived from `vote_id` plus the sorted tied option ids, so the same
+/// tie always resolves the same way.
+pub fn resolve_tie(vote_id: u32, tied: &[u32], strategy: TieStrategy, earlier_stage: impl Fn(u32) -> i64) -> Vec<u32> {
+    let mut ids = tied.to_vec();
+    match strategy {
+        TieStrategy::Forwards => ids.sort_by_key(|&id| (std::cmp::Reverse(earlier_stage(id)), id)),
+        TieStrategy::Backwards => ids.sort_by_key(|&id| (earlier_stage(id), id)),
+        TieStrategy::Random => {
+            // Canonical order first, so the seed alone (not insertion order)
+            // determines the shuffle result.
+            ids.sort_unstable();
+            let seed = format!("zvote-tie:{}:{:?}", vote_id, ids);
+            deterministic_shuffle(&mut ids, seed);
+        }
+    }
+    ids
+}
@@ -1,13 +1,17 @@
 use spacetimedb::{ReducerContext, SpacetimeType, Table, Identity, Timestamp, Filter, client_visibility_filter};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use blake3;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::utils::normalize_label;
 // Bring table traits into scope for method resolution on `ctx.db.*()`.
 use crate::approval::approval;
 use crate::judgment::judgment;
 use crate::judgment::mj_summary as mj_summary_table;
+use crate::judgment::mj_ranking as mj_ranking_table;
+use crate::vote_access::vote_access as vote_access_table;
+use crate::stv::ranked_ballot;
+use crate::stv::stv_result;
 
 // Maximum number of options allowed per vote (server-enforced)
 pub const MAX_OPTIONS: usize = 20;
@@ -24,15 +28,48 @@ pub const ENABLE_PRIVATE_VOTES: bool = false;
 // Voting systems
 pub const ENABLE_APPROVAL_VOTING: bool = true;
 pub const ENABLE_MAJORITY_JUDGMENT: bool = true;
+pub const ENABLE_STV_VOTING: bool = false;
 
 // Ballot submission modes
 pub const ENABLE_LIVE_BALLOT: bool = true;    // Submit changes immediately
 pub const ENABLE_ENVELOPE_BALLOT: bool = true; // Batch submit all at once
+pub const ENABLE_SEALED_BALLOT: bool = false;  // Commit-reveal, hides the running tally
+
+// Per-vote delegation (liquid democracy)
+pub const ENABLE_DELEGATION: bool = false;
 
 #[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum VotingSystem {
     Approval,
     MajorityJudgment,
+    /// Ranked-choice voting counted by Single Transferable Vote (Droop
+    /// quota, configurable surplus-transfer method). See `crate::stv`.
+    SingleTransferableVote,
+}
+
+/// Lifecycle stage of a vote. Votes start as `Draft` or `Open` (the caller's
+/// choice at creation time); `Closed`/`Finalized` are only ever reached by
+/// `close_vote` or the scheduled `finalize_vote_on_schedule` reducer.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VoteStatus {
+    /// Not yet accepting ballots. Does not change who can see the vote:
+    /// that still follows `visibility` alone.
+    Draft,
+    /// Accepting ballots, subject to `opens_at`/`closes_at`.
+    Open,
+    /// No longer accepting ballots; tally snapshot not written yet.
+    Closed,
+    /// Tally snapshot written to `vote_result`. Terminal.
+    Finalized,
+}
+
+/// Who may cast a ballot in a vote.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VotingRestriction {
+    /// Anyone who can see the vote may cast a ballot.
+    Open,
+    /// Only identities listed in `authorized_voter` may cast a ballot.
+    Allowlist,
 }
 
 // Visibility levels as integers for RLS-friendly SQL comparisons
@@ -61,9 +98,504 @@ pub struct Vote {
     pub created_at: Timestamp,
     pub token: String,
     pub voting_system: VotingSystem,
+    pub voting_restriction: VotingRestriction,
+    /// Optional moment before which no ballots may be cast, even while
+    /// `status` is `Open`.
+    pub opens_at: Option<Timestamp>,
+    /// Optional deadline after which no ballots may be cast. Does not close
+    /// the vote by itself - `close_vote` (or the scheduled reducer this
+    /// field registers) still needs to observe it.
+    pub closes_at: Option<Timestamp>,
+    /// Minimum number of distinct voters required for the result to count.
+    /// Used both by `quorum_met` at close time and by `compute_outcome`.
+    pub quorum: Option<u32>,
+    /// Approval-vote pass threshold, as a percentage (0-100) of total
+    /// ballots an option's approvals must reach. Read by `compute_outcome`;
+    /// `None` means outcomes are purely informational (counts only).
+    pub approval_threshold: Option<u8>,
+    /// Only meaningful for `VotingSystem::SingleTransferableVote`: number of
+    /// seats to fill. `None` defaults to 1 (single-winner STV).
+    pub stv_seats: Option<u32>,
+    /// Only meaningful for STV: which surplus-transfer method
+    /// `recompute_stv_for_vote` uses once a candidate passes the Droop
+    /// quota. `None` defaults to Weighted Inclusive Gregory.
+    pub stv_surplus_method: Option<crate::stv::SurplusMethod>,
+    /// Only meaningful for STV: how many decimal places of precision the
+    /// fixed-point transfer values are rounded to. `None` defaults to 4.
+    pub stv_decimal_places: Option<u8>,
+    /// Which deterministic strategy breaks ties between options with
+    /// otherwise equal standing (equal MJ majority gauge, or equal lowest
+    /// STV tally). `None` defaults to `Forwards`. See `crate::tie_break`.
+    pub tie_strategy: Option<crate::tie_break::TieStrategy>,
+    /// Current lifecycle stage. Kept alongside `closed` below for backward
+    /// compatibility with existing readers; `is_vote_closed` is the single
+    /// source of truth for whether ballots are currently accepted.
+    pub status: VoteStatus,
+    /// Set once `close_vote` (or the scheduled close) has run; after that, no further ballots.
+    pub closed: bool,
+    /// Whether `quorum` was met at close time. Meaningless while `closed` is false.
+    pub quorum_met: bool,
+    /// True when this vote uses the sealed (commit-reveal) ballot mode
+    /// instead of live/envelope submission.
+    pub sealed: bool,
+    /// Current phase of a sealed vote. `None` for non-sealed votes.
+    pub phase: Option<crate::sealed_ballot::BallotPhase>,
+}
+
+/// Return true if `vote` does not currently accept ballots: its `status` is
+/// not `Open`, `ctx.timestamp` is still before `opens_at`, or it was
+/// explicitly closed / has passed its `closes_at` deadline.
+pub fn is_vote_closed(ctx: &ReducerContext, vote: &Vote) -> bool {
+    if vote.status != VoteStatus::Open {
+        return true;
+    }
+    if vote.opens_at.map_or(false, |t| ctx.timestamp < t) {
+        return true;
+    }
+    vote.closed || vote.closes_at.map_or(false, |t| ctx.timestamp >= t)
+}
+
+// Authorized voters for votes in Allowlist mode. Irrelevant (but harmless)
+// for Open votes.
+#[spacetimedb::table(
+    name = authorized_voter,
+    public,
+    index(name = by_vote, btree(columns = [vote_id])),
+    index(name = by_vote_and_voter, btree(columns = [vote_id, voter]))
+)]
+pub struct AuthorizedVoter {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub voter: Identity,
+}
+
+/// Return true if `voter` is allowed to cast a ballot in `vote`.
+pub fn is_voter_authorized(ctx: &ReducerContext, vote: &Vote, voter: Identity) -> bool {
+    match vote.voting_restriction {
+        VotingRestriction::Open => true,
+        VotingRestriction::Allowlist => ctx
+            .db
+            .authorized_voter()
+            .by_vote_and_voter()
+            .filter((vote.id, voter))
+            .next()
+            .is_some(),
+    }
+}
+
+/// Add `voter` to the allowlist for `vote_id`. Creator-only.
+#[spacetimedb::reducer]
+pub fn add_authorized_voter(ctx: &ReducerContext, vote_id: u32, voter: Identity) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.creator != ctx.sender {
+        return Err("Only the vote creator can manage the authorized voter list".into());
+    }
+    let already_listed = ctx
+        .db
+        .authorized_voter()
+        .by_vote_and_voter()
+        .filter((vote_id, voter))
+        .next()
+        .is_some();
+    if !already_listed {
+        ctx.db.authorized_voter().insert(AuthorizedVoter { id: 0, vote_id, voter });
+    }
+    Ok(())
+}
+
+/// Remove `voter` from the allowlist for `vote_id`. Creator-only.
+#[spacetimedb::reducer]
+pub fn remove_authorized_voter(ctx: &ReducerContext, vote_id: u32, voter: Identity) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.creator != ctx.sender {
+        return Err("Only the vote creator can manage the authorized voter list".into());
+    }
+    if let Some(row) = ctx
+        .db
+        .authorized_voter()
+        .by_vote_and_voter()
+        .filter((vote_id, voter))
+        .next()
+    {
+        ctx.db.authorized_voter().delete(row);
+    }
+    Ok(())
+}
+
+// ================================
+// Append-only ballot audit trail
+// ================================
+
+/// What kind of change a `BallotEvent` records.
+#[derive(SpacetimeType, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BallotEventKind {
+    /// A voter's first ballot entry for an option.
+    Cast,
+    /// An existing ballot entry was updated (e.g. a changed MJ mention).
+    Change,
+    /// A ballot entry was removed.
+    Withdraw,
+}
+
+// Immutable log of every ballot mutation, appended to by `approval` and
+// `judgment` - never updated or deleted (except wholesale, by `delete_vote`).
+// Public so clients can subscribe per `vote_id` and independently verify the
+// published tally via `finalize_audit`.
+#[spacetimedb::table(
+    name = ballot_event,
+    public,
+    index(name = by_vote, btree(columns = [vote_id])),
+    index(name = by_vote_and_voter, btree(columns = [vote_id, voter]))
+)]
+pub struct BallotEvent {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub voter: Identity,
+    pub event_kind: BallotEventKind,
+    /// The affected option, or `None` for an event that spans every option
+    /// of the vote (e.g. a full `withdraw_judgments`).
+    pub option_id: Option<u32>,
+    /// Free-form detail needed to replay the event - e.g. the MJ mention
+    /// that was cast. Unused (empty) for approval events, which carry all
+    /// the state they need in `event_kind` + `option_id`.
+    pub payload: String,
+    pub at: Timestamp,
+}
+
+/// Append one row to the ballot audit trail. Called by every ballot-mutating
+/// reducer in `approval`/`judgment`.
+pub(crate) fn append_ballot_event(
+    ctx: &ReducerContext,
+    vote_id: u32,
+    voter: Identity,
+    event_kind: BallotEventKind,
+    option_id: Option<u32>,
+    payload: String,
+) {
+    ctx.db.ballot_event().insert(BallotEvent {
+        id: 0,
+        vote_id,
+        voter,
+        event_kind,
+        option_id,
+        payload,
+        at: ctx.timestamp,
+    });
+}
+
+/// Replay `vote_id`'s event log from scratch and compare the result against
+/// the live counters (`VoteOption::approvals_count` for Approval,
+/// `mj_summary` for Majority Judgment). Errors with a description of the
+/// first mismatch found - evidence of a bug or tampering, since the log is
+/// append-only and should always agree with the tables it was used to build.
+#[spacetimedb::reducer]
+pub fn finalize_audit(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+
+    match vote.voting_system {
+        VotingSystem::Approval => audit_approval(ctx, vote_id),
+        VotingSystem::MajorityJudgment => audit_judgment(ctx, vote_id),
+        // STV has no incremental counter to diverge from `ballot_event` -
+        // `stv_result` is always a full recompute from `ranked_ballot`, so
+        // its "audit" is recomputing and diffing against what's stored.
+        VotingSystem::SingleTransferableVote => crate::stv::audit_stv(ctx, vote_id),
+    }
+}
+
+fn audit_approval(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    let mut replayed: HashSet<(Identity, u32)> = HashSet::new();
+    for ev in ctx.db.ballot_event().by_vote().filter(vote_id) {
+        let Some(option_id) = ev.option_id else { continue };
+        match ev.event_kind {
+            BallotEventKind::Cast | BallotEventKind::Change => {
+                replayed.insert((ev.voter, option_id));
+            }
+            BallotEventKind::Withdraw => {
+                replayed.remove(&(ev.voter, option_id));
+            }
+        }
+    }
+
+    for opt in get_vote_options(ctx, vote_id) {
+        let replayed_count = replayed.iter().filter(|(_, oid)| *oid == opt.id).count() as u32;
+        if replayed_count != opt.approvals_count {
+            return Err(format!(
+                "Audit mismatch for option {}: event log replays to {} approvals but the live counter reads {}",
+                opt.id, replayed_count, opt.approvals_count
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Relies on `mj_summary` counting only direct ballots - `ballot_event` never
+/// records delegation-derived ballots, so if `mj_summary` ever folded
+/// delegation weight in too this replay would permanently, falsely mismatch
+/// for any vote using delegation. See the comment on
+/// `judgment::recompute_mj_summary_for_vote`.
+fn audit_judgment(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    let mut replayed: std::collections::HashMap<(Identity, u32), crate::judgment::Mention> = std::collections::HashMap::new();
+    for ev in ctx.db.ballot_event().by_vote().filter(vote_id) {
+        match ev.event_kind {
+            BallotEventKind::Cast | BallotEventKind::Change => {
+                let Some(option_id) = ev.option_id else { continue };
+                let Some(mention) = crate::judgment::mention_from_str(&ev.payload) else { continue };
+                replayed.insert((ev.voter, option_id), mention);
+            }
+            BallotEventKind::Withdraw => match ev.option_id {
+                Some(option_id) => {
+                    replayed.remove(&(ev.voter, option_id));
+                }
+                None => replayed.retain(|(voter, _), _| *voter != ev.voter),
+            },
+        }
+    }
+
+    for opt in get_vote_options(ctx, vote_id) {
+        let mut counts = [0u32; 7];
+        let mut total = 0u32;
+        for ((_, option_id), mention) in replayed.iter() {
+            if *option_id == opt.id {
+                counts[*mention as usize] += 1;
+                total += 1;
+            }
+        }
+
+        let live = ctx.db.mj_summary().option_id().find(opt.id);
+        let live_counts = live
+            .as_ref()
+            .map(|s| [s.bad, s.inadequate, s.passable, s.fair, s.good, s.very_good, s.excellent])
+            .unwrap_or([0; 7]);
+        let live_total = live.as_ref().map(|s| s.total).unwrap_or(0);
+
+        if total != live_total || counts != live_counts {
+            return Err(format!(
+                "Audit mismatch for option {}: event log replays to {} total judgments but mj_summary reads {}",
+                opt.id, total, live_total
+            ));
+        }
+    }
+    Ok(())
 }
 
+// ================================
+// Per-vote delegation (liquid democracy)
+// ================================
+//
+// Unlike `approval::Delegation` (which lets a delegatee explicitly cast on
+// a delegator's behalf), this delegation is resolved at tally time and
+// works uniformly across voting systems: a delegator who has not cast a
+// direct ballot simply contributes a copy of whichever ballot their chain
+// resolves to.
 
+// One row per (vote_id, delegator). Chains are allowed: A -> B -> C means
+// A and B both adopt C's ballot once resolved at tally time.
+#[spacetimedb::table(
+    name = vote_delegation,
+    public,
+    index(name = by_vote, btree(columns = [vote_id])),
+    index(name = by_vote_and_delegator, btree(columns = [vote_id, delegator])),
+    index(name = by_vote_and_delegate, btree(columns = [vote_id, delegate]))
+)]
+pub struct VoteDelegation {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub delegator: Identity,
+    pub delegate: Identity,
+    pub ts: Timestamp,
+    /// Set when this delegation's chain is, or leads into, a cycle, so it contributes
+    /// no ballot at tally time. Recomputed alongside `delegation_weight`.
+    pub in_cycle: bool,
+}
+
+// RLS: a client sees delegations where it is either party
+#[client_visibility_filter]
+const VOTE_DELEGATION_RLS: Filter = Filter::Sql(
+    "SELECT vote_delegation.* FROM vote_delegation WHERE vote_delegation.delegator = :sender OR vote_delegation.delegate = :sender"
+);
+
+fn find_vote_delegation(ctx: &ReducerContext, vote_id: u32, delegator: Identity) -> Option<VoteDelegation> {
+    ctx.db
+        .vote_delegation()
+        .by_vote_and_delegator()
+        .filter((vote_id, delegator))
+        .next()
+}
+
+/// Walk `start`'s delegation chain for `vote_id` to its terminal identity
+/// (the first identity with no delegation of its own), guarding against
+/// cycles with a visited set. Returns `None` if the chain revisits an
+/// identity (`start` is in a cycle, or its chain leads into one) -
+/// per spec, every identity in a cycle is dropped from the tally.
+pub(crate) fn resolve_delegation_chain(ctx: &ReducerContext, vote_id: u32, start: Identity) -> Option<Identity> {
+    let mut current = start;
+    let mut visited: HashSet<Identity> = HashSet::new();
+    visited.insert(current);
+    loop {
+        let Some(d) = find_vote_delegation(ctx, vote_id, current) else {
+            return Some(current);
+        };
+        if !visited.insert(d.delegate) {
+            return None;
+        }
+        current = d.delegate;
+    }
+}
+
+/// Resolve the ballot-casting identity that should count on `voter`'s
+/// behalf: `voter` itself if they cast a direct ballot (a direct ballot
+/// always overrides delegation), otherwise whoever their delegation chain
+/// resolves to - but only if that terminal identity is itself a direct
+/// voter. A delegation to someone who never voted, or into a cycle,
+/// contributes nothing.
+fn resolve_effective_voter(
+    ctx: &ReducerContext,
+    vote_id: u32,
+    voter: Identity,
+    direct_voters: &HashSet<Identity>,
+) -> Option<Identity> {
+    if direct_voters.contains(&voter) {
+        return Some(voter);
+    }
+    match resolve_delegation_chain(ctx, vote_id, voter) {
+        Some(terminal) if direct_voters.contains(&terminal) => Some(terminal),
+        _ => None,
+    }
+}
+
+/// Delegate the caller's ballot for `vote_id` to `delegate`. Replaces any
+/// existing delegation the caller has for this vote.
+#[spacetimedb::reducer]
+pub fn set_delegation(ctx: &ReducerContext, vote_id: u32, delegate: Identity) -> Result<(), String> {
+    if !ENABLE_DELEGATION {
+        return Err("Delegation is not enabled on this server".into());
+    }
+    if find_vote_by_id(ctx, vote_id).is_none() {
+        return Err("Vote not found".into());
+    }
+    if delegate == ctx.sender {
+        return Err("Cannot delegate a vote to yourself".into());
+    }
+
+    if let Some(existing) = find_vote_delegation(ctx, vote_id, ctx.sender) {
+        ctx.db.vote_delegation().id().update(VoteDelegation {
+            delegate,
+            ts: ctx.timestamp,
+            in_cycle: false,
+            ..existing
+        });
+    } else {
+        ctx.db.vote_delegation().insert(VoteDelegation {
+            id: 0,
+            vote_id,
+            delegator: ctx.sender,
+            delegate,
+            ts: ctx.timestamp,
+            in_cycle: false,
+        });
+    }
+    recompute_delegation_weights(ctx, vote_id);
+    Ok(())
+}
+
+/// Clear the caller's delegation for `vote_id`, if any.
+#[spacetimedb::reducer]
+pub fn clear_delegation(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    if let Some(existing) = find_vote_delegation(ctx, vote_id, ctx.sender) {
+        ctx.db.vote_delegation().delete(existing);
+        recompute_delegation_weights(ctx, vote_id);
+    }
+    Ok(())
+}
+
+// ================================
+// Clear Ballot Terminology Aliases
+// ================================
+//
+// `VoteDelegation` above predates this alias and was deliberately built
+// system-agnostic rather than MJ-specific, so it backs Approval and STV the
+// same way. These aliases just give judgment ballots the same clearer
+// delegation verbs the other ballot actions have. Live MJ tallies
+// (`mj_summary`/`mj_ranking`) count direct ballots only and do not fold
+// delegated ballots in; those count only at close/outcome time - see
+// `recompute_delegation_weights` below and
+// `judgment::recompute_mj_summary_for_vote`.
+
+/// Delegate the caller's judgment ballot for `vote_id` to `delegate` (clearer
+/// alias for `set_delegation`).
+#[spacetimedb::reducer]
+pub fn delegate_vote(ctx: &ReducerContext, vote_id: u32, delegate: Identity) -> Result<(), String> {
+    set_delegation(ctx, vote_id, delegate)
+}
+
+/// Withdraw the caller's delegation for `vote_id`, reverting to
+/// self-representation (clearer alias for `clear_delegation`).
+#[spacetimedb::reducer]
+pub fn undelegate_vote(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    clear_delegation(ctx, vote_id)
+}
+
+// Precomputed delegation weight per delegate, so clients can display voting
+// weight without resolving chains themselves. Recomputed whenever a
+// delegation in the vote is set or cleared, or whenever a direct ballot is
+// cast (a direct ballot can override an outgoing delegation via
+// self-representation, which changes who counts as a chain's terminal).
+#[spacetimedb::table(
+    name = delegation_weight,
+    public,
+    index(name = by_vote, btree(columns = [vote_id]))
+)]
+pub struct DelegationWeight {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub delegate: Identity,
+    pub count: u32,
+}
+
+/// Recompute `delegation_weight` (purely a display aid) for `vote_id`.
+/// Called from `set_delegation`/`clear_delegation` and from every
+/// direct-ballot-casting/withdrawing reducer (`approve`/`unapprove`,
+/// `cast_judgment`/`withdraw_judgments`, `cast_ranked_ballot`/
+/// `withdraw_ranked_ballot`), since any of these can change which voter a
+/// delegation chain resolves to. Deliberately does not touch `mj_summary` -
+/// delegation is folded into the live tally only at close/outcome time via
+/// `compute_effective_tally`/`judgment::effective_mj_gauges`, so it is
+/// counted exactly once (see the comment on
+/// `judgment::recompute_mj_summary_for_vote`).
+pub(crate) fn recompute_delegation_weights(ctx: &ReducerContext, vote_id: u32) {
+    for row in ctx.db.delegation_weight().by_vote().filter(vote_id) {
+        ctx.db.delegation_weight().delete(row);
+    }
+
+    let mut counts: HashMap<Identity, u32> = HashMap::new();
+    for d in ctx.db.vote_delegation().by_vote().filter(vote_id) {
+        let terminal = resolve_delegation_chain(ctx, vote_id, d.delegator);
+        let in_cycle = terminal.is_none();
+        if d.in_cycle != in_cycle {
+            ctx.db.vote_delegation().id().update(VoteDelegation { in_cycle, ..d });
+        }
+        if let Some(terminal) = terminal {
+            *counts.entry(terminal).or_insert(0) += 1;
+        }
+    }
+    for (delegate, count) in counts {
+        ctx.db.delegation_weight().insert(DelegationWeight { id: 0, vote_id, delegate, count });
+    }
+}
 
 // RLS DEBUGGING: Testing public filter only
 #[client_visibility_filter]
@@ -143,11 +675,50 @@ pub fn create_vote(
     options: Vec<String>,
     visibility: Option<u8>,
     voting_system: Option<VotingSystem>,
+    voting_restriction: Option<VotingRestriction>,
+    opens_at: Option<Timestamp>,
+    closes_at: Option<Timestamp>,
+    quorum: Option<u32>,
+    approval_threshold: Option<u8>,
+    sealed: Option<bool>,
+    status: Option<VoteStatus>,
+    stv_seats: Option<u32>,
+    stv_surplus_method: Option<crate::stv::SurplusMethod>,
+    stv_decimal_places: Option<u8>,
+    tie_strategy: Option<crate::tie_break::TieStrategy>,
 ) -> Result<(), String> {
     let title = normalize_label(&title)?;
 
     let cleaned = validate_and_clean_options(options)?;
 
+    if let (Some(o), Some(c)) = (opens_at, closes_at) {
+        if o >= c {
+            return Err("opens_at must be before closes_at".into());
+        }
+    }
+    if approval_threshold.map_or(false, |t| t > 100) {
+        return Err("approval_threshold must be between 0 and 100".into());
+    }
+    if stv_seats.map_or(false, |s| s == 0) {
+        return Err("stv_seats must be at least 1".into());
+    }
+    if stv_decimal_places.map_or(false, |p| p > 9) {
+        return Err("stv_decimal_places must be between 0 and 9".into());
+    }
+    let status = status.unwrap_or(VoteStatus::Open);
+    match status {
+        VoteStatus::Draft | VoteStatus::Open => {}
+        VoteStatus::Closed | VoteStatus::Finalized => {
+            return Err("A vote cannot be created already Closed or Finalized".into());
+        }
+    }
+
+    let sealed = sealed.unwrap_or(false);
+    if sealed && !ENABLE_SEALED_BALLOT {
+        return Err("Sealed (commit-reveal) ballots are not enabled on this server".to_string());
+    }
+    let phase = sealed.then_some(crate::sealed_ballot::BallotPhase::Commit);
+
     // Resolve visibility (default to Public if enabled, otherwise first available)
     let vis = visibility.unwrap_or(VISIBILITY_PUBLIC);
     
@@ -174,9 +745,14 @@ pub fn create_vote(
         VotingSystem::MajorityJudgment if !ENABLE_MAJORITY_JUDGMENT => {
             return Err("Majority Judgment is not enabled on this server".to_string());
         }
+        VotingSystem::SingleTransferableVote if !ENABLE_STV_VOTING => {
+            return Err("Ranked-choice (STV) voting is not enabled on this server".to_string());
+        }
         _ => {}
     }
 
+    let restriction = voting_restriction.unwrap_or(VotingRestriction::Open);
+
     // Pre-generate a unique token before inserting the vote
     let temp_vote_for_token = Vote {
         id: 0, // Temp value, will be auto-incremented on insert
@@ -186,6 +762,20 @@ pub fn create_vote(
         created_at: ctx.timestamp,
         token: String::new(), // Placeholder
         voting_system: system,
+        voting_restriction: restriction,
+        opens_at,
+        closes_at,
+        quorum,
+        approval_threshold,
+        stv_seats,
+        stv_surplus_method,
+        stv_decimal_places,
+        tie_strategy,
+        status,
+        closed: false,
+        quorum_met: false,
+        sealed,
+        phase,
     };
 
     let mut token = compute_share_token(ctx, &temp_vote_for_token, 0);
@@ -203,6 +793,20 @@ pub fn create_vote(
         created_at: ctx.timestamp,
         token,
         voting_system: system,
+        voting_restriction: restriction,
+        opens_at,
+        closes_at,
+        quorum,
+        approval_threshold,
+        stv_seats,
+        stv_surplus_method,
+        stv_decimal_places,
+        tie_strategy,
+        status,
+        closed: false,
+        quorum_met: false,
+        sealed,
+        phase,
     });
 
     for (idx, label) in cleaned.into_iter().enumerate() {
@@ -214,6 +818,46 @@ pub fn create_vote(
             order_index: idx as u32,
         });
     }
+
+    // Schedule the automatic close/finalize for votes with a deadline.
+    if let Some(closes_at) = closes_at {
+        ctx.db.vote_close_schedule().insert(VoteCloseSchedule {
+            scheduled_id: 0,
+            scheduled_at: closes_at.into(),
+            vote_id: vote.id,
+        });
+    }
+
+    Ok(())
+}
+
+// Schedules the automatic close/finalize of a vote at its `closes_at` time.
+// One row per vote that was created with a deadline.
+#[spacetimedb::table(
+    name = vote_close_schedule,
+    scheduled(finalize_vote_on_schedule),
+    index(name = by_vote, btree(columns = [vote_id]))
+)]
+pub struct VoteCloseSchedule {
+    #[primary_key]
+    #[auto_inc]
+    scheduled_id: u64,
+    scheduled_at: spacetimedb::ScheduleAt,
+    vote_id: u32,
+}
+
+/// Scheduled reducer: fires at a vote's `closes_at`, closing and finalizing
+/// it automatically. A no-op if the vote was deleted, or already
+/// closed/finalized by a manual `close_vote` call, before the schedule fired.
+#[spacetimedb::reducer]
+pub fn finalize_vote_on_schedule(ctx: &ReducerContext, args: VoteCloseSchedule) -> Result<(), String> {
+    let Some(vote) = ctx.db.vote().id().find(args.vote_id) else {
+        return Ok(());
+    };
+    if vote.status == VoteStatus::Closed || vote.status == VoteStatus::Finalized {
+        return Ok(());
+    }
+    finalize_vote(ctx, vote);
     Ok(())
 }
 
@@ -233,10 +877,14 @@ pub struct ServerInfo {
     // Voting systems
     enable_approval_voting: bool,
     enable_majority_judgment: bool,
-    
+    enable_stv_voting: bool,
+
     // Ballot submission modes
     enable_live_ballot: bool,
     enable_envelope_ballot: bool,
+    enable_sealed_ballot: bool,
+
+    enable_delegation: bool,
 }
 
 /// Ensure the ServerInfo singleton row exists (id=1), seeding server capabilities.
@@ -255,15 +903,275 @@ pub fn ensure_server_info(ctx: &ReducerContext) -> Result<(), String> {
             // Voting systems
             enable_approval_voting: ENABLE_APPROVAL_VOTING,
             enable_majority_judgment: ENABLE_MAJORITY_JUDGMENT,
-            
+            enable_stv_voting: ENABLE_STV_VOTING,
+
             // Ballot submission modes
             enable_live_ballot: ENABLE_LIVE_BALLOT,
             enable_envelope_ballot: ENABLE_ENVELOPE_BALLOT,
+            enable_sealed_ballot: ENABLE_SEALED_BALLOT,
+
+            enable_delegation: ENABLE_DELEGATION,
         });
     }
     Ok(())
 }
 
+// Immutable per-option snapshot of a vote's final tally, written once by
+// `finalize_vote` (from `close_vote` or the scheduled close). Gives clients a stable result instead of a live-updating
+// counter once the voting window is over.
+#[spacetimedb::table(
+    name = vote_result,
+    public,
+    index(name = by_vote, btree(columns = [vote_id]))
+)]
+pub struct VoteResult {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub option_id: u32,
+    pub approvals_count: u32,
+    /// Only meaningful for Majority Judgment votes: true for the option(s)
+    /// ranked 1 by the majority gauge (median grade, then the usual-judgment
+    /// tie-break); options with an equal gauge share the win.
+    pub is_mj_winner: bool,
+}
+
+// Reducer: close a vote and freeze its tally. Creator-only.
+#[spacetimedb::reducer]
+pub fn close_vote(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    let Some(vote) = ctx.db.vote().id().find(vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.creator != ctx.sender {
+        return Err("Only the vote creator can close this vote".into());
+    }
+    if vote.status == VoteStatus::Closed || vote.status == VoteStatus::Finalized {
+        return Err("Vote is already closed".into());
+    }
+
+    finalize_vote(ctx, vote);
+    Ok(())
+}
+
+/// Shared close+tally logic used by both the manual `close_vote` reducer and
+/// the scheduled `finalize_vote_on_schedule` reducer: computes quorum,
+/// writes the frozen `vote_result` snapshot, and marks the vote `Finalized`.
+fn finalize_vote(ctx: &ReducerContext, vote: Vote) {
+    let vote_id = vote.id;
+
+    let tally = compute_effective_tally(ctx, &vote);
+    let quorum_met = vote.quorum.map_or(true, |q| tally.effective_voters.len() as u32 >= q);
+
+    let options: Vec<_> = get_vote_options(ctx, vote_id).collect();
+    // Rank 1 of the real median-based ranking (with its usual-judgment
+    // tie-break), folding in delegated ballots exactly once - not the old
+    // ordinal-average `mj_score` heuristic mj_ranking was built to supersede.
+    let mj_ranks: HashMap<u32, u32> = if vote.voting_system == VotingSystem::MajorityJudgment {
+        let strategy = vote.tie_strategy.unwrap_or(crate::tie_break::TieStrategy::Forwards);
+        let gauges = crate::judgment::effective_mj_gauges(ctx, vote_id, &tally.delegated_voters);
+        crate::judgment::dense_rank_options(vote_id, strategy, gauges)
+            .into_iter()
+            .filter(|(_, _, grade, _)| grade.is_some())
+            .map(|(option_id, rank, _, _)| (option_id, rank))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    for opt in &options {
+        let is_mj_winner = vote.voting_system == VotingSystem::MajorityJudgment && mj_ranks.get(&opt.id) == Some(&1);
+
+        let mut approvals_count = opt.approvals_count;
+        if vote.voting_system == VotingSystem::Approval {
+            for voter in &tally.delegated_voters {
+                if ctx
+                    .db
+                    .approval()
+                    .by_vote_voter_option()
+                    .filter((vote_id, *voter, opt.id))
+                    .next()
+                    .is_some()
+                {
+                    approvals_count = approvals_count.saturating_add(1);
+                }
+            }
+        }
+
+        ctx.db.vote_result().insert(VoteResult {
+            id: 0,
+            vote_id,
+            option_id: opt.id,
+            approvals_count,
+            is_mj_winner,
+        });
+    }
+
+    if vote.sealed {
+        crate::sealed_ballot::purge_unrevealed_commitments(ctx, vote_id);
+    }
+
+    if vote.voting_system == VotingSystem::SingleTransferableVote {
+        crate::stv::recompute_stv_for_vote(ctx, vote_id);
+    }
+
+    recompute_vote_outcome(ctx, &vote, &tally);
+
+    ctx.db.vote().id().update(Vote {
+        status: VoteStatus::Finalized,
+        closed: true,
+        quorum_met,
+        phase: vote.sealed.then_some(crate::sealed_ballot::BallotPhase::Closed),
+        ..vote
+    });
+}
+
+/// Direct and delegation-resolved voters for a vote, shared by `finalize_vote`
+/// and `compute_outcome` so both agree on who counts as having voted.
+struct EffectiveTally {
+    effective_voters: HashSet<Identity>,
+    /// One entry per delegator whose chain resolved to a direct voter,
+    /// naming that direct voter (with repeats - a popular delegate's ballot
+    /// is counted once per delegator who adopted it).
+    delegated_voters: Vec<Identity>,
+}
+
+fn compute_effective_tally(ctx: &ReducerContext, vote: &Vote) -> EffectiveTally {
+    let direct_voters: HashSet<Identity> = match vote.voting_system {
+        VotingSystem::Approval => ctx.db.approval().by_vote().filter(vote.id).map(|a| a.voter).collect(),
+        VotingSystem::MajorityJudgment => get_vote_options(ctx, vote.id)
+            .flat_map(|opt| ctx.db.judgment().by_option().filter(opt.id).map(|j| j.voter))
+            .collect(),
+        VotingSystem::SingleTransferableVote => ctx
+            .db
+            .ranked_ballot()
+            .by_vote()
+            .filter(vote.id)
+            .map(|b| b.voter)
+            .collect(),
+    };
+
+    let mut effective_voters: HashSet<Identity> = direct_voters.clone();
+    let mut delegated_voters: Vec<Identity> = Vec::new();
+    if ENABLE_DELEGATION {
+        for d in ctx.db.vote_delegation().by_vote().filter(vote.id) {
+            if direct_voters.contains(&d.delegator) {
+                continue; // a direct ballot overrides delegation
+            }
+            if let Some(resolved) = resolve_effective_voter(ctx, vote.id, d.delegator, &direct_voters) {
+                effective_voters.insert(d.delegator);
+                delegated_voters.push(resolved);
+            }
+        }
+    }
+
+    EffectiveTally { effective_voters, delegated_voters }
+}
+
+// Decision outcome per option: whether it meets the creator's pass criteria
+// (`approval_threshold` for Approval, the majority grade for MJ), alongside
+// the ballot/quorum facts that criteria was judged against. Recomputed by
+// `compute_outcome` - manually, or automatically when a scheduled close
+// fires - so it's purely derived and safe to throw away and rebuild.
+#[spacetimedb::table(
+    name = vote_outcome,
+    public,
+    index(name = by_vote, btree(columns = [vote_id]))
+)]
+pub struct VoteOutcome {
+    #[auto_inc]
+    #[primary_key]
+    id: u64,
+    pub vote_id: u32,
+    pub option_id: u32,
+    pub total_ballots: u32,
+    pub quorum_met: bool,
+    pub passed: bool,
+}
+
+/// Write (or refresh) the `vote_outcome` snapshot for `vote`. With zero
+/// ballots everything fails and `quorum_met` is false. Otherwise, once
+/// quorum is met: with no `approval_threshold` every Approval option passes
+/// (even one with zero approvals); MJ options pass on a majority grade of
+/// Fair or better and STV options when elected, whatever is configured.
+fn recompute_vote_outcome(ctx: &ReducerContext, vote: &Vote, tally: &EffectiveTally) {
+    for row in ctx.db.vote_outcome().by_vote().filter(vote.id) {
+        ctx.db.vote_outcome().delete(row);
+    }
+
+    let total_ballots = tally.effective_voters.len() as u32;
+    let quorum_met = total_ballots > 0 && vote.quorum.map_or(true, |q| total_ballots >= q);
+
+    // Per-option majority gauge (median grade), delegation folded in exactly
+    // once - shared by the `passed` check below and by `finalize_vote`'s
+    // winner determination, so "passes" and "wins" agree on the same grade.
+    let mj_gauges: HashMap<u32, Option<(crate::judgment::Mention, u32, u32)>> =
+        if vote.voting_system == VotingSystem::MajorityJudgment {
+            crate::judgment::effective_mj_gauges(ctx, vote.id, &tally.delegated_voters)
+                .into_iter()
+                .map(|(option_id, gauge, _)| (option_id, gauge))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+    for opt in get_vote_options(ctx, vote.id) {
+        let passed = total_ballots > 0 && quorum_met && match vote.voting_system {
+            VotingSystem::Approval => {
+                let mut approvals = opt.approvals_count;
+                for voter in &tally.delegated_voters {
+                    if ctx
+                        .db
+                        .approval()
+                        .by_vote_voter_option()
+                        .filter((vote.id, *voter, opt.id))
+                        .next()
+                        .is_some()
+                    {
+                        approvals = approvals.saturating_add(1);
+                    }
+                }
+                match vote.approval_threshold {
+                    Some(threshold) => approvals as u64 * 100 >= threshold as u64 * total_ballots as u64,
+                    None => true,
+                }
+            }
+            VotingSystem::MajorityJudgment => {
+                // Passes when the real majority grade (median mention) is at
+                // or above Fair, the midpoint grade.
+                mj_gauges
+                    .get(&opt.id)
+                    .and_then(|gauge| gauge.as_ref())
+                    .map(|(grade, _, _)| *grade >= crate::judgment::Mention::Fair)
+                    .unwrap_or(false)
+            }
+            VotingSystem::SingleTransferableVote => crate::stv::is_elected(ctx, vote.id, opt.id),
+        };
+
+        ctx.db.vote_outcome().insert(VoteOutcome {
+            id: 0,
+            vote_id: vote.id,
+            option_id: opt.id,
+            total_ballots,
+            quorum_met,
+            passed,
+        });
+    }
+}
+
+/// Recompute and store the `vote_outcome` snapshot for `vote_id`. Safe to
+/// call at any time, before or after the vote closes - it reflects the
+/// ballot state at the moment it's called, not necessarily a final one.
+#[spacetimedb::reducer]
+pub fn compute_outcome(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    let tally = compute_effective_tally(ctx, &vote);
+    recompute_vote_outcome(ctx, &vote, &tally);
+    Ok(())
+}
+
 // Reducer: delete a vote (only creator can delete)
 #[spacetimedb::reducer]
 pub fn delete_vote(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
@@ -276,11 +1184,55 @@ pub fn delete_vote(ctx: &ReducerContext, vote_id: u32) -> Result<(), String> {
         for s in ctx.db.mj_summary().by_vote().filter(vote_id) {
             ctx.db.mj_summary().delete(s);
         }
+        // Delete the precomputed MJ ranking for this vote
+        for r in ctx.db.mj_ranking().by_vote().filter(vote_id) {
+            ctx.db.mj_ranking().delete(r);
+        }
 
         // Delete approvals
         for a in ctx.db.approval().by_vote().filter(vote_id) {
             ctx.db.approval().delete(a);
         }
+        // Delete the authorized-voter allowlist, if any
+        for av in ctx.db.authorized_voter().by_vote().filter(vote_id) {
+            ctx.db.authorized_voter().delete(av);
+        }
+        // Delete vote_access grants for this vote, if any
+        for grant in ctx.db.vote_access().by_vote().filter(vote_id) {
+            ctx.db.vote_access().delete(grant);
+        }
+        // Delete the frozen result snapshot, if the vote was ever closed
+        for r in ctx.db.vote_result().by_vote().filter(vote_id) {
+            ctx.db.vote_result().delete(r);
+        }
+        // Cancel the scheduled auto-close, if one was registered
+        for sched in ctx.db.vote_close_schedule().by_vote().filter(vote_id) {
+            ctx.db.vote_close_schedule().delete(sched);
+        }
+        // Delete per-vote delegations and their precomputed weights, if any
+        for d in ctx.db.vote_delegation().by_vote().filter(vote_id) {
+            ctx.db.vote_delegation().delete(d);
+        }
+        for w in ctx.db.delegation_weight().by_vote().filter(vote_id) {
+            ctx.db.delegation_weight().delete(w);
+        }
+        // Delete the decision-outcome snapshot, if one was ever computed
+        for o in ctx.db.vote_outcome().by_vote().filter(vote_id) {
+            ctx.db.vote_outcome().delete(o);
+        }
+        // Delete the ballot audit trail, if any
+        for ev in ctx.db.ballot_event().by_vote().filter(vote_id) {
+            ctx.db.ballot_event().delete(ev);
+        }
+        // Delete ranked-choice (STV) ballots and their precomputed rounds, if any
+        for rb in ctx.db.ranked_ballot().by_vote().filter(vote_id) {
+            ctx.db.ranked_ballot().delete(rb);
+        }
+        for sr in ctx.db.stv_result().by_vote().filter(vote_id) {
+            ctx.db.stv_result().delete(sr);
+        }
+        // Delete sealed-ballot commitments, if any
+        crate::sealed_ballot::delete_commitments_for_vote(ctx, vote_id);
         // Delete options and their associated judgments
         for opt in ctx.db.vote_option().by_vote().filter(vote_id) {
             // Delete judgments for this option
@@ -0,0 +1,120 @@
+use spacetimedb::{ReducerContext, Table, Identity, Timestamp, Filter, client_visibility_filter};
+use crate::vote::{find_vote_by_id, find_vote_by_token, VISIBILITY_UNLISTED};
+
+// VoteAccess table: tracks which users have access to which unlisted votes
+// Public with RLS: clients only see their own access grants
+#[spacetimedb::table(
+    name = vote_access,
+    public,
+    index(name = by_vote, btree(columns = [vote_id])),
+    index(name = by_user, btree(columns = [user_id])),
+    index(name = by_vote_and_user, btree(columns = [vote_id, user_id]))
+)]
+pub struct VoteAccess {
+    #[primary_key]
+    #[auto_inc]
+    id: u64,
+    pub vote_id: u32,
+    pub user_id: Identity,
+    /// When this grant stops being valid. `None` means it never expires.
+    pub expires_at: Option<Timestamp>,
+}
+
+// RLS: a user can only see their own access grants. `:sender` is the only
+// parameter SpacetimeDB RLS rules support in this codebase (see every other
+// `Filter::Sql` in approval.rs/judgment.rs/stv.rs/vote.rs) - there is no
+// `:current_time` equivalent, so expiry is NOT filtered here. A subscribed
+// client may still see an expired grant row; `has_unexpired_access` below is
+// the actual read/query-boundary enforcement every caller must go through
+// before treating a grant as valid.
+#[client_visibility_filter]
+const VOTE_ACCESS_RLS: Filter = Filter::Sql(
+    "SELECT vote_access.* FROM vote_access WHERE vote_access.user_id = :sender"
+);
+
+/// The authoritative expiry check: callers must use this (not raw row
+/// presence) to decide whether a grant is still valid, since the RLS filter
+/// above cannot itself filter on the current time.
+pub(crate) fn has_unexpired_access(ctx: &ReducerContext, vote_id: u32, user_id: Identity) -> bool {
+    ctx.db
+        .vote_access()
+        .by_vote_and_user()
+        .filter((vote_id, user_id))
+        .any(|a| a.expires_at.map_or(true, |exp| exp > ctx.timestamp))
+}
+
+// Reducer: grant access to an unlisted vote via token, optionally expiring after `ttl`.
+#[spacetimedb::reducer]
+pub fn grant_access_by_token(ctx: &ReducerContext, token: String, ttl: Option<spacetimedb::TimeDuration>) -> Result<(), String> {
+    // 1. Find the vote by the provided token
+    let Some(vote) = find_vote_by_token(ctx, &token) else {
+        return Err("Vote not found".into());
+    };
+
+    // 2. Only grant access to unlisted votes
+    if vote.visibility != VISIBILITY_UNLISTED {
+        return Ok(()); // Not an error, just a no-op for public/private votes
+    }
+
+    // 3. Don't grant access to the creator, they already have it
+    if vote.creator == ctx.sender {
+        return Ok(());
+    }
+
+    // 4. Check if the user already has an unexpired grant to avoid duplicate entries
+    if has_unexpired_access(ctx, vote.id, ctx.sender) {
+        return Ok(());
+    }
+
+    // 5. Grant access by inserting a new row
+    ctx.db.vote_access().insert(VoteAccess {
+        id: 0, // auto-incremented
+        vote_id: vote.id,
+        user_id: ctx.sender,
+        expires_at: ttl.map(|d| ctx.timestamp + d),
+    });
+
+    Ok(())
+}
+
+/// Proactively grant a specific identity access to an unlisted vote, without
+/// requiring them to redeem a share token themselves. Creator-only.
+#[spacetimedb::reducer]
+pub fn grant_access_to_user(ctx: &ReducerContext, vote_id: u32, user_id: Identity, ttl: Option<spacetimedb::TimeDuration>) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.creator != ctx.sender {
+        return Err("Only the vote creator can grant access to this vote".into());
+    }
+    if vote.visibility != VISIBILITY_UNLISTED {
+        return Ok(()); // Not an error, just a no-op for public/private votes
+    }
+    if has_unexpired_access(ctx, vote_id, user_id) {
+        return Ok(());
+    }
+
+    ctx.db.vote_access().insert(VoteAccess {
+        id: 0,
+        vote_id,
+        user_id,
+        expires_at: ttl.map(|d| ctx.timestamp + d),
+    });
+
+    Ok(())
+}
+
+/// Revoke a previously granted access. Creator-only.
+#[spacetimedb::reducer]
+pub fn revoke_access(ctx: &ReducerContext, vote_id: u32, user_id: Identity) -> Result<(), String> {
+    let Some(vote) = find_vote_by_id(ctx, vote_id) else {
+        return Err("Vote not found".into());
+    };
+    if vote.creator != ctx.sender {
+        return Err("Only the vote creator can revoke access to this vote".into());
+    }
+    for grant in ctx.db.vote_access().by_vote_and_user().filter((vote_id, user_id)) {
+        ctx.db.vote_access().delete(grant);
+    }
+    Ok(())
+}